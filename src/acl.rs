@@ -0,0 +1,173 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Privilege {
+    All,
+    Read,
+    Write,
+    WriteProperties,
+    WriteContent,
+    Unlock,
+    Bind,
+    Unbind,
+    ReadAcl,
+    WriteAcl,
+    ReadCurrentUserPrivilegeSet,
+    ReadFreeBusy,
+    ScheduleDeliver,
+    ScheduleDeliverInvite,
+    ScheduleDeliverReply,
+    ScheduleQueryFreebusy,
+    ScheduleSend,
+    ScheduleSendInvite,
+    ScheduleSendReply,
+    ScheduleSendFreebusy,
+}
+
+impl Privilege {
+    const ALL: [Self; 20] = [
+        Self::All,
+        Self::Read,
+        Self::Write,
+        Self::WriteProperties,
+        Self::WriteContent,
+        Self::Unlock,
+        Self::Bind,
+        Self::Unbind,
+        Self::ReadAcl,
+        Self::WriteAcl,
+        Self::ReadCurrentUserPrivilegeSet,
+        Self::ReadFreeBusy,
+        Self::ScheduleDeliver,
+        Self::ScheduleDeliverInvite,
+        Self::ScheduleDeliverReply,
+        Self::ScheduleQueryFreebusy,
+        Self::ScheduleSend,
+        Self::ScheduleSendInvite,
+        Self::ScheduleSendReply,
+        Self::ScheduleSendFreebusy,
+    ];
+
+    fn element(&self) -> &'static str {
+        match self {
+            Self::All => "d:all",
+            Self::Read => "d:read",
+            Self::Write => "d:write",
+            Self::WriteProperties => "d:write-properties",
+            Self::WriteContent => "d:write-content",
+            Self::Unlock => "d:unlock",
+            Self::Bind => "d:bind",
+            Self::Unbind => "d:unbind",
+            Self::ReadAcl => "d:read-acl",
+            Self::WriteAcl => "d:write-acl",
+            Self::ReadCurrentUserPrivilegeSet => "d:read-current-user-privilege-set",
+            Self::ReadFreeBusy => "cal:read-free-busy",
+            Self::ScheduleDeliver => "cal:schedule-deliver",
+            Self::ScheduleDeliverInvite => "cal:schedule-deliver-invite",
+            Self::ScheduleDeliverReply => "cal:schedule-deliver-reply",
+            Self::ScheduleQueryFreebusy => "cal:schedule-query-freebusy",
+            Self::ScheduleSend => "cal:schedule-send",
+            Self::ScheduleSendInvite => "cal:schedule-send-invite",
+            Self::ScheduleSendReply => "cal:schedule-send-reply",
+            Self::ScheduleSendFreebusy => "cal:schedule-send-freebusy",
+        }
+    }
+
+    /**
+     * The aggregate privilege containing this one (RFC 3744 §3.12, RFC 4791
+     * §6.1.1 and RFC 6638 §6).
+     */
+    fn parent(&self) -> Option<Self> {
+        match self {
+            Self::All => None,
+            Self::WriteProperties | Self::WriteContent | Self::Bind | Self::Unbind => {
+                Some(Self::Write)
+            }
+            Self::ReadFreeBusy => Some(Self::Read),
+            Self::ScheduleDeliverInvite
+            | Self::ScheduleDeliverReply
+            | Self::ScheduleQueryFreebusy => Some(Self::ScheduleDeliver),
+            Self::ScheduleSendInvite | Self::ScheduleSendReply | Self::ScheduleSendFreebusy => {
+                Some(Self::ScheduleSend)
+            }
+            _ => Some(Self::All),
+        }
+    }
+
+    /**
+     * Reads the privileges found under `xpath`.
+     */
+    pub(crate) fn from_response<X>(response: &str, xpath: &str) -> Vec<Self>
+    where
+        X: crate::Xmlable,
+    {
+        Self::ALL
+            .into_iter()
+            .filter(|x| !X::xml(response, &format!("{xpath}/{}", x.element())).is_empty())
+            .collect()
+    }
+}
+
+/**
+ * The `current-user-privilege-set` of a resource (RFC 3744 §5.4).
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Privileges(pub Vec<Privilege>);
+
+impl Privileges {
+    /**
+     * Checks if `privilege` is granted, directly or through an aggregate
+     * privilege.
+     */
+    pub fn contains(&self, privilege: Privilege) -> bool {
+        let mut current = Some(privilege);
+
+        while let Some(privilege) = current {
+            if self.0.contains(&privilege) {
+                return true;
+            }
+
+            current = privilege.parent();
+        }
+
+        false
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.contains(Privilege::Read)
+    }
+
+    /**
+     * Objects can be created, modified and removed.
+     */
+    pub fn can_write(&self) -> bool {
+        self.contains(Privilege::WriteContent)
+            && self.contains(Privilege::Bind)
+            && self.contains(Privilege::Unbind)
+    }
+
+    pub fn can_read_free_busy(&self) -> bool {
+        self.contains(Privilege::ReadFreeBusy)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AcePrincipal {
+    Href(String),
+    All,
+    Authenticated,
+    Unauthenticated,
+    Itself,
+    Property,
+}
+
+/**
+ * An access control entry of the `DAV:acl` property (RFC 3744 §5.5).
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ace {
+    pub principal: AcePrincipal,
+    pub invert: bool,
+    pub grant: Vec<Privilege>,
+    pub deny: Vec<Privilege>,
+    pub protected: bool,
+    pub inherited: Option<String>,
+}
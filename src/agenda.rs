@@ -0,0 +1,137 @@
+use chrono::TimeZone;
+use std::collections::BTreeMap;
+
+/**
+ * When the agenda stops: after a number of occurrences or at a date.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AgendaLimit {
+    Count(usize),
+    Until(chrono::DateTime<chrono::Utc>),
+}
+
+/**
+ * An event, or one instance of a recurring event.
+ */
+#[derive(Clone, Debug)]
+pub struct Occurrence<Tz>
+where
+    Tz: chrono::TimeZone,
+{
+    pub calendar: String,
+    pub color: Option<String>,
+    pub uid: Option<String>,
+    pub recurrence_id: Option<String>,
+    pub summary: Option<String>,
+    pub location: Option<String>,
+    pub start: chrono::DateTime<Tz>,
+    pub end: chrono::DateTime<Tz>,
+    /**
+     * All-day occurrences start and end at midnight in the agenda timezone.
+     */
+    pub all_day: bool,
+}
+
+impl<Tz> Occurrence<Tz>
+where
+    Tz: chrono::TimeZone,
+{
+    fn new(
+        name: &str,
+        calendar: &crate::Calendar,
+        event: &crate::ics::Component,
+        tz: &Tz,
+    ) -> Option<Self> {
+        if event
+            .value("STATUS")
+            .is_some_and(|x| x.eq_ignore_ascii_case("CANCELLED"))
+        {
+            return None;
+        }
+
+        let all_day = event.property("DTSTART")?.is_date();
+        let (start, end) = crate::ics::bounds(event)?;
+
+        let local = |date_time: chrono::DateTime<chrono::Utc>| {
+            if all_day {
+                tz.from_local_datetime(&date_time.naive_utc())
+                    .earliest()
+                    .unwrap_or_else(|| date_time.with_timezone(tz))
+            } else {
+                date_time.with_timezone(tz)
+            }
+        };
+        let text = |name: &str| event.value(name).map(crate::ics::unescape_text);
+
+        Some(Self {
+            calendar: name.to_string(),
+            color: calendar.color.clone(),
+            uid: event.value("UID").map(str::to_string),
+            recurrence_id: event.value("RECURRENCE-ID").map(str::to_string),
+            summary: text("SUMMARY"),
+            location: text("LOCATION"),
+            start: local(start),
+            end: local(end),
+            all_day,
+        })
+    }
+}
+
+/**
+ * With a count, the searched period grows from a week up to a year until
+ * enough occurrences are found.
+ */
+pub(crate) fn agenda<Tz>(
+    calendars: &BTreeMap<String, crate::Calendar>,
+    from: chrono::DateTime<Tz>,
+    limit: AgendaLimit,
+) -> crate::Result<Vec<Occurrence<Tz>>>
+where
+    Tz: chrono::TimeZone,
+{
+    let tz = from.timezone();
+    let start = from.with_timezone(&chrono::Utc);
+    let mut window = chrono::Duration::days(7);
+
+    loop {
+        let end = match limit {
+            AgendaLimit::Count(_) => start + window,
+            AgendaLimit::Until(until) => until,
+        };
+
+        let mut occurrences = Vec::new();
+
+        for (name, calendar) in calendars {
+            for object in calendar.expand("VEVENT", start, end)? {
+                let data = object.data.unwrap_or_default();
+
+                for vcalendar in crate::ics::parse(&data) {
+                    let events = vcalendar
+                        .children("VEVENT")
+                        .filter_map(|x| Occurrence::new(name, calendar, x, &tz))
+                        .filter(|x| x.end > from || x.start >= from);
+
+                    occurrences.extend(events);
+                }
+            }
+        }
+
+        occurrences.sort_by(|a, b| {
+            a.start
+                .cmp(&b.start)
+                .then_with(|| a.calendar.cmp(&b.calendar))
+        });
+
+        match limit {
+            AgendaLimit::Count(count)
+                if occurrences.len() >= count || window >= chrono::Duration::days(366) =>
+            {
+                occurrences.truncate(count);
+
+                return Ok(occurrences);
+            }
+            AgendaLimit::Count(_) => window = (window * 4).min(chrono::Duration::days(366)),
+            AgendaLimit::Until(_) => return Ok(occurrences),
+        }
+    }
+}
@@ -0,0 +1,351 @@
+/**
+ * A reminder of an event or a task, one per repetition.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alarm {
+    /**
+     * URL of the calendar object.
+     */
+    pub href: String,
+    pub uid: Option<String>,
+    pub recurrence_id: Option<String>,
+    /**
+     * `UID` of the `VALARM` (RFC 9074 §4).
+     */
+    pub alarm_uid: Option<String>,
+    pub action: String,
+    pub description: Option<String>,
+    pub summary: Option<String>,
+    pub trigger: chrono::DateTime<chrono::Utc>,
+    /**
+     * 0 for the first trigger, then the number of the `REPEAT`.
+     */
+    pub repetition: u32,
+    /**
+     * Position of the `VALARM` in its component, to find an alarm without
+     * `UID`.
+     */
+    pub(crate) index: usize,
+}
+
+/**
+ * Alarms of the events and tasks of `data` triggering between `start` and
+ * `end`.
+ */
+pub(crate) fn evaluate(
+    href: &str,
+    data: &str,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+) -> Vec<Alarm> {
+    let mut alarms = Vec::new();
+
+    for vcalendar in crate::ics::parse(data) {
+        let timezones = crate::Timezones::embedded(&vcalendar);
+        let components = vcalendar
+            .components
+            .iter()
+            .filter(|x| x.name == "VEVENT" || x.name == "VTODO");
+
+        for component in components {
+            for (index, valarm) in component.children("VALARM").enumerate() {
+                alarms.extend(triggers(
+                    href, &timezones, component, valarm, index, start, end,
+                ));
+            }
+        }
+    }
+
+    alarms
+}
+
+/**
+ * Sorts by trigger time and removes the alarms found twice, an absolute
+ * trigger is copied in every expanded occurrence.
+ */
+pub(crate) fn sort(mut alarms: Vec<Alarm>) -> Vec<Alarm> {
+    alarms.sort_by(|a, b| {
+        a.trigger
+            .cmp(&b.trigger)
+            .then_with(|| a.href.cmp(&b.href))
+            .then_with(|| a.alarm_uid.cmp(&b.alarm_uid))
+    });
+    alarms
+        .dedup_by(|a, b| a.trigger == b.trigger && a.href == b.href && a.alarm_uid == b.alarm_uid);
+
+    alarms
+}
+
+fn triggers(
+    href: &str,
+    timezones: &crate::Timezones,
+    component: &crate::ics::Component,
+    valarm: &crate::ics::Component,
+    index: usize,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+) -> Vec<Alarm> {
+    // Location based alarms can't be evaluated from the time
+    if valarm.property("PROXIMITY").is_some() {
+        return Vec::new();
+    }
+
+    let Some(base) = trigger(timezones, component, valarm) else {
+        return Vec::new();
+    };
+
+    let acknowledged = valarm
+        .value("ACKNOWLEDGED")
+        .and_then(crate::ics::parse_date_time);
+
+    // The repetitions are acknowledged with the alarm (RFC 9074 §6)
+    if acknowledged.is_some_and(|x| x >= base) {
+        return Vec::new();
+    }
+
+    let interval = valarm
+        .value("DURATION")
+        .and_then(crate::ics::parse_duration);
+    let repeat = match interval {
+        Some(interval) if interval > chrono::Duration::zero() => valarm
+            .value("REPEAT")
+            .and_then(|x| x.trim().parse::<u32>().ok())
+            .unwrap_or(0),
+        _ => 0,
+    };
+
+    let text = |component: &crate::ics::Component, name: &str| {
+        component.value(name).map(crate::ics::unescape_text)
+    };
+
+    let mut alarms = Vec::new();
+    let mut trigger = base;
+
+    for repetition in 0..=repeat {
+        if trigger >= end {
+            break;
+        }
+
+        if trigger >= start {
+            alarms.push(Alarm {
+                href: href.to_string(),
+                uid: component.value("UID").map(str::to_string),
+                recurrence_id: component.value("RECURRENCE-ID").map(str::to_string),
+                alarm_uid: valarm.value("UID").map(str::to_string),
+                action: valarm.value("ACTION").unwrap_or("DISPLAY").to_uppercase(),
+                description: text(valarm, "DESCRIPTION"),
+                summary: text(component, "SUMMARY"),
+                trigger,
+                repetition,
+                index,
+            });
+        }
+
+        trigger += interval.unwrap_or_default();
+    }
+
+    alarms
+}
+
+/**
+ * The first trigger time: absolute, or relative to the start or the end of
+ * the component (RFC 5545 §3.8.6.3).
+ */
+fn trigger(
+    timezones: &crate::Timezones,
+    component: &crate::ics::Component,
+    valarm: &crate::ics::Component,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let trigger = valarm.property("TRIGGER")?;
+
+    let absolute = trigger
+        .param("VALUE")
+        .is_some_and(|x| x.eq_ignore_ascii_case("DATE-TIME"));
+
+    if absolute || !trigger.value.contains('P') {
+        return crate::ics::parse_date_time(&trigger.value);
+    }
+
+    let offset = crate::ics::parse_duration(&trigger.value)?;
+
+    let related_end = trigger
+        .param("RELATED")
+        .is_some_and(|x| x.eq_ignore_ascii_case("END"));
+
+    let anchor = match timezones.component_bounds(component) {
+        Some((start, end)) => {
+            if related_end {
+                end
+            } else {
+                start
+            }
+        }
+        // A task without start
+        None => component
+            .property("DUE")
+            .and_then(|x| timezones.property(x))?,
+    };
+
+    Some(anchor + offset)
+}
+
+/**
+ * Marks the `VALARM` of `alarm` as acknowledged at `now` (RFC 9074 §6).
+ */
+pub(crate) fn acknowledge(
+    data: &str,
+    alarm: &Alarm,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<String> {
+    let mut calendars = crate::ics::parse(data);
+    let component = component(&mut calendars, alarm)?;
+    let i = valarm(component, alarm)?;
+
+    set(
+        &mut component.components[i],
+        "ACKNOWLEDGED",
+        &crate::ics::format_date_time(&now),
+    );
+
+    Some(calendars.iter().map(ToString::to_string).collect())
+}
+
+/**
+ * Acknowledges the `VALARM` of `alarm` and adds a snooze alarm triggering at
+ * `until` (RFC 9074 §5). Snoozing a snooze alarm replaces it.
+ */
+pub(crate) fn snooze(
+    data: &str,
+    alarm: &Alarm,
+    until: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<String> {
+    let mut calendars = crate::ics::parse(data);
+    let component = component(&mut calendars, alarm)?;
+    let i = valarm(component, alarm)?;
+
+    let original = &component.components[i];
+    let action = original.value("ACTION").unwrap_or("DISPLAY").to_string();
+    let description = original.value("DESCRIPTION").map(str::to_string);
+    let snoozed = original
+        .properties("RELATED-TO")
+        .find(|x| {
+            x.param("RELTYPE")
+                .is_some_and(|x| x.eq_ignore_ascii_case("SNOOZE"))
+        })
+        .map(|x| x.value.clone());
+
+    let related = match snoozed {
+        Some(related) => {
+            component.components.remove(i);
+            related
+        }
+        None => {
+            let original = &mut component.components[i];
+            set(
+                original,
+                "ACKNOWLEDGED",
+                &crate::ics::format_date_time(&now),
+            );
+
+            match original.value("UID") {
+                Some(uid) => uid.to_string(),
+                None => {
+                    let uid = crate::ics::uid();
+                    original.push("UID", &[], &uid);
+                    uid
+                }
+            }
+        }
+    };
+
+    let mut snooze = crate::ics::Component::new("VALARM");
+    snooze.push("UID", &[], &crate::ics::uid());
+    snooze.push("ACTION", &[], &action);
+    snooze.push(
+        "TRIGGER",
+        &[("VALUE", "DATE-TIME")],
+        &crate::ics::format_date_time(&until),
+    );
+    snooze.push("RELATED-TO", &[("RELTYPE", "SNOOZE")], &related);
+
+    if let Some(description) = description {
+        snooze.push("DESCRIPTION", &[], &description);
+    }
+
+    component.components.push(snooze);
+
+    Some(calendars.iter().map(ToString::to_string).collect())
+}
+
+/**
+ * The component of `alarm`: the overridden occurrence if there is one,
+ * otherwise the master component. The `RECURRENCE-ID`s are compared as
+ * instants, the expanded ones are in UTC while the stored ones usually have a
+ * `TZID`.
+ */
+fn component<'a>(
+    calendars: &'a mut [crate::ics::Component],
+    alarm: &Alarm,
+) -> Option<&'a mut crate::ics::Component> {
+    let timezones: Vec<_> = calendars.iter().map(crate::Timezones::embedded).collect();
+    let occurrence = alarm
+        .recurrence_id
+        .as_deref()
+        .map(crate::ics::parse_date_time);
+
+    let matches = |x: &crate::ics::Component,
+                   timezones: &crate::Timezones,
+                   occurrence: Option<Option<chrono::DateTime<chrono::Utc>>>| {
+        (x.name == "VEVENT" || x.name == "VTODO")
+            && x.value("UID") == alarm.uid.as_deref()
+            && x.property("RECURRENCE-ID").map(|x| timezones.property(x)) == occurrence
+    };
+
+    let overridden = occurrence.is_some()
+        && calendars
+            .iter()
+            .zip(&timezones)
+            .any(|(calendar, timezones)| {
+                calendar
+                    .components
+                    .iter()
+                    .any(|x| matches(x, timezones, occurrence))
+            });
+
+    let occurrence = if overridden { occurrence } else { None };
+
+    calendars
+        .iter_mut()
+        .zip(&timezones)
+        .flat_map(|(calendar, timezones)| {
+            calendar.components.iter_mut().map(move |x| (x, timezones))
+        })
+        .find(|(x, timezones)| matches(x, timezones, occurrence))
+        .map(|(x, _)| x)
+}
+
+/**
+ * Position of the `VALARM` of `alarm` in the components of `component`.
+ */
+fn valarm(component: &crate::ics::Component, alarm: &Alarm) -> Option<usize> {
+    let mut valarms = component
+        .components
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.name == "VALARM");
+
+    let found = match &alarm.alarm_uid {
+        Some(uid) => valarms.find(|(_, x)| x.value("UID") == Some(uid)),
+        None => valarms.nth(alarm.index),
+    };
+
+    found.map(|(i, _)| i)
+}
+
+fn set(component: &mut crate::ics::Component, name: &str, value: &str) {
+    component
+        .properties
+        .retain(|x| !x.name.eq_ignore_ascii_case(name));
+    component.push(name, &[], value);
+}
@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+
+/**
+ * Key/value store used to persist calendar data between boots.
+ */
+pub trait Storage {
+    fn get(&self, key: &str) -> crate::Result<Option<Vec<u8>>>;
+    fn set(&mut self, key: &str, value: &[u8]) -> crate::Result;
+    fn remove(&mut self, key: &str) -> crate::Result;
+}
+
+impl<S> Storage for &mut S
+where
+    S: Storage + ?Sized,
+{
+    fn get(&self, key: &str) -> crate::Result<Option<Vec<u8>>> {
+        (**self).get(key)
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> crate::Result {
+        (**self).set(key, value)
+    }
+
+    fn remove(&mut self, key: &str) -> crate::Result {
+        (**self).remove(key)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    values: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> crate::Result<Option<Vec<u8>>> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> crate::Result {
+        self.values.insert(key.to_string(), value.to_vec());
+
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> crate::Result {
+        self.values.remove(key);
+
+        Ok(())
+    }
+}
+
+/**
+ * One file per key in `root`, works on a mounted SPIFFS or FAT partition as
+ * well as on a desktop filesystem.
+ */
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    root: std::path::PathBuf,
+}
+
+impl FileStorage {
+    pub fn new<P>(root: P) -> crate::Result<Self>
+    where
+        P: Into<std::path::PathBuf>,
+    {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+
+        Ok(Self { root })
+    }
+
+    /**
+     * Keys are hashed: SPIFFS and FAT without long file names have short
+     * file names.
+     */
+    fn path(&self, key: &str) -> std::path::PathBuf {
+        self.root.join(format!("{:016x}", hash(key)))
+    }
+}
+
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> crate::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> crate::Result {
+        std::fs::write(self.path(key), value)?;
+
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> crate::Result {
+        match std::fs::remove_file(self.path(key)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/**
+ * Stores values as blobs in a NVS namespace.
+ */
+pub struct NvsStorage<T>
+where
+    T: esp_idf_svc::nvs::NvsPartitionId,
+{
+    nvs: esp_idf_svc::nvs::EspNvs<T>,
+}
+
+impl<T> NvsStorage<T>
+where
+    T: esp_idf_svc::nvs::NvsPartitionId,
+{
+    pub fn new(nvs: esp_idf_svc::nvs::EspNvs<T>) -> Self {
+        Self { nvs }
+    }
+
+    /**
+     * NVS keys are limited to 15 characters.
+     */
+    fn key(key: &str) -> String {
+        format!("k{:014x}", hash(key) >> 8)
+    }
+}
+
+impl<T> Storage for NvsStorage<T>
+where
+    T: esp_idf_svc::nvs::NvsPartitionId,
+{
+    fn get(&self, key: &str) -> crate::Result<Option<Vec<u8>>> {
+        let key = Self::key(key);
+
+        let Some(len) = self.nvs.blob_len(&key).map_err(crate::Error::storage)? else {
+            return Ok(None);
+        };
+
+        let mut buf = vec![0; len];
+        let value = self
+            .nvs
+            .get_raw(&key, &mut buf)
+            .map_err(crate::Error::storage)?
+            .map(<[u8]>::to_vec);
+
+        Ok(value)
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> crate::Result {
+        self.nvs
+            .set_raw(&Self::key(key), value)
+            .map_err(crate::Error::storage)?;
+
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> crate::Result {
+        self.nvs
+            .remove(&Self::key(key))
+            .map_err(crate::Error::storage)?;
+
+        Ok(())
+    }
+}
+
+/**
+ * FNV-1a, stable between builds unlike the `std` hasher.
+ */
+fn hash(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/**
+ * Calendar objects stored with their ETag, so only the changed ones are
+ * downloaded again.
+ */
+pub struct Cache<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> Cache<S>
+where
+    S: Storage,
+{
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    pub(crate) fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    pub(crate) fn storage_mut(&mut self) -> &mut S {
+        &mut self.storage
+    }
+
+    /**
+     * ETags of the cached objects of a calendar, by href.
+     */
+    pub(crate) fn entries(&self, calendar: &str) -> crate::Result<BTreeMap<String, String>> {
+        let Some(index) = self.storage.get(&format!("index:{calendar}"))? else {
+            return Ok(BTreeMap::new());
+        };
+
+        let entries = String::from_utf8_lossy(&index)
+            .lines()
+            .filter_map(|x| x.split_once('\t'))
+            .map(|(href, etag)| (href.to_string(), etag.to_string()))
+            .collect();
+
+        Ok(entries)
+    }
+
+    pub(crate) fn set_entries(
+        &mut self,
+        calendar: &str,
+        entries: &BTreeMap<String, String>,
+    ) -> crate::Result {
+        let index = entries
+            .iter()
+            .map(|(href, etag)| format!("{href}\t{etag}\n"))
+            .collect::<String>();
+
+        self.storage
+            .set(&format!("index:{calendar}"), index.as_bytes())
+    }
+
+    /**
+     * The cached data of `href` if it's still at `etag`.
+     */
+    pub(crate) fn object(&self, href: &str, etag: &str) -> crate::Result<Option<String>> {
+        let data = self
+            .get(href)?
+            .filter(|(cached, _)| cached == etag)
+            .map(|(_, data)| data);
+
+        Ok(data)
+    }
+
+    /**
+     * The cached ETag and data of `href`, whatever the ETag.
+     */
+    pub(crate) fn get(&self, href: &str) -> crate::Result<Option<(String, String)>> {
+        let Some(value) = self.storage.get(&format!("object:{href}"))? else {
+            return Ok(None);
+        };
+
+        let value = String::from_utf8_lossy(&value);
+
+        let entry = value
+            .split_once('\n')
+            .map(|(etag, data)| (etag.to_string(), data.to_string()));
+
+        Ok(entry)
+    }
+
+    pub(crate) fn store(&mut self, href: &str, etag: &str, data: &str) -> crate::Result {
+        self.storage.set(
+            &format!("object:{href}"),
+            format!("{etag}\n{data}").as_bytes(),
+        )
+    }
+
+    pub(crate) fn remove(&mut self, href: &str) -> crate::Result {
+        self.storage.remove(&format!("object:{href}"))
+    }
+}
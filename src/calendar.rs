@@ -1,14 +1,66 @@
 use crate::Children;
 use crate::Requestable;
+use crate::Xmlable;
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug, Default, crate::Object)]
 pub struct Calendar {
     pub color: Option<String>,
+    pub ctag: Option<String>,
+    pub sync_token: Option<String>,
+    /**
+     * `calendar-timezone-id` (RFC 7809 §5.2).
+     */
+    pub timezone_id: Option<String>,
     url: String,
     auth: Option<crate::Authorization>,
+    pub(crate) components: Vec<String>,
+    pub(crate) offline: Option<std::sync::Arc<crate::Snapshot>>,
+    pub(crate) taken: Option<chrono::DateTime<chrono::Utc>>,
+    pub(crate) timezones_by_reference: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CalendarProperties {
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub order: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertyError {
+    pub name: String,
+    pub status: u16,
+}
+
+/**
+ * Result of [`Calendar::pull`]: the objects of the calendar and the hrefs
+ * downloaded or removed from the cache.
+ */
+#[derive(Debug, Default)]
+pub(crate) struct Pull {
+    pub objects: Vec<crate::Object>,
+    pub fetched: Vec<String>,
+    pub removed: Vec<String>,
 }
 
 impl Calendar {
+    /**
+     * The sync token, or the ctag for older servers, changes every time an
+     * object of the calendar changes.
+     */
+    pub fn tag(&self) -> Option<&str> {
+        self.sync_token.as_deref().or(self.ctag.as_deref())
+    }
+
+    /**
+     * When the calendar was saved, if it comes from an offline snapshot.
+     */
+    pub fn snapshot(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.taken
+    }
+
     pub fn objects(&self) -> crate::Result<crate::object::Iterator> {
         let response = self.request(None)?;
 
@@ -17,22 +69,396 @@ impl Calendar {
         ))
     }
 
+    /**
+     * Downloads every event, see [`Calendar::events_cached`] to only download
+     * the changed ones.
+     */
     pub fn events(&self) -> crate::Result<crate::object::Iterator> {
-        let response = self.request(Some("VEVENT"))?;
-
-        Ok(crate::object::Iterator::from(
-            self.to_vec(&response, "//d:response/d:href/text()"),
-        ))
+        self.component("VEVENT")
     }
 
     pub fn tasks(&self) -> crate::Result<crate::object::Iterator> {
-        let response = self.request(Some("VTODO"))?;
+        self.component("VTODO")
+    }
+
+    pub fn journals(&self) -> crate::Result<crate::object::Iterator> {
+        self.component("VJOURNAL")
+    }
+
+    /**
+     * Like [`Calendar::events`], but only the events changed since they were
+     * stored in `cache` are downloaded.
+     */
+    pub fn events_cached<S>(
+        &self,
+        cache: &mut crate::Cache<S>,
+    ) -> crate::Result<crate::object::Iterator>
+    where
+        S: crate::Storage,
+    {
+        self.component_cached("VEVENT", cache)
+    }
+
+    /**
+     * Like [`Calendar::tasks`], with the unchanged tasks taken from `cache`.
+     */
+    pub fn tasks_cached<S>(
+        &self,
+        cache: &mut crate::Cache<S>,
+    ) -> crate::Result<crate::object::Iterator>
+    where
+        S: crate::Storage,
+    {
+        self.component_cached("VTODO", cache)
+    }
+
+    /**
+     * Like [`Calendar::journals`], with the unchanged journals taken from
+     * `cache`.
+     */
+    pub fn journals_cached<S>(
+        &self,
+        cache: &mut crate::Cache<S>,
+    ) -> crate::Result<crate::object::Iterator>
+    where
+        S: crate::Storage,
+    {
+        self.component_cached("VJOURNAL", cache)
+    }
+
+    fn component_cached<S>(
+        &self,
+        component: &str,
+        cache: &mut crate::Cache<S>,
+    ) -> crate::Result<crate::object::Iterator>
+    where
+        S: crate::Storage,
+    {
+        if !self.supports(component) {
+            return Ok(crate::object::Iterator::from(Vec::new()));
+        }
+
+        let pull = self.pull(Some(component), cache)?;
+
+        Ok(crate::object::Iterator::from(pull.objects))
+    }
+
+    /**
+     * Brings `cache` up to date with the server: objects with a new ETag are
+     * downloaded and the removed ones forgotten. Without `component`, every
+     * object of the calendar is synchronized.
+     */
+    pub(crate) fn pull<S>(
+        &self,
+        component: Option<&str>,
+        cache: &mut crate::Cache<S>,
+    ) -> crate::Result<Pull>
+    where
+        S: crate::Storage,
+    {
+        let filter = component
+            .map(|x| format!(r#"<c:comp-filter name="{x}" />"#))
+            .unwrap_or_default();
+
+        let body = format!(
+            r#"
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <d:getetag />
+    </d:prop>
+    <c:filter>
+        <c:comp-filter name="VCALENDAR">
+            {filter}
+        </c:comp-filter>
+    </c:filter>
+</c:calendar-query>
+"#
+        );
+
+        let response = self.query(&body)?;
+        let remote = crate::Object::from_response(self, &response);
+
+        let mut objects = Vec::new();
+        let mut changed = Vec::new();
+
+        for mut object in remote {
+            let cached = match &object.etag {
+                Some(etag) => cache.object(object.url(), etag)?,
+                None => None,
+            };
+
+            match cached {
+                Some(data) => {
+                    object.data = Some(data);
+                    objects.push(object);
+                }
+                None => changed.push(object),
+            }
+        }
+
+        let fetched = self.multiget(&changed)?;
+
+        for object in &fetched {
+            if let (Some(etag), Some(data)) = (&object.etag, &object.data) {
+                cache.store(object.url(), etag, data)?;
+            }
+        }
+
+        let mut pull = Pull {
+            fetched: fetched.iter().map(|x| x.url().to_string()).collect(),
+            ..Default::default()
+        };
+
+        objects.extend(fetched);
+
+        let mut entries = BTreeMap::new();
+
+        for object in &objects {
+            if let Some(etag) = &object.etag {
+                entries.insert(object.url().to_string(), etag.clone());
+            }
+        }
+
+        for href in cache.entries(&self.url)?.keys() {
+            if !entries.contains_key(href) {
+                cache.remove(href)?;
+                pull.removed.push(href.clone());
+            }
+        }
+
+        cache.set_entries(&self.url, &entries)?;
+        pull.objects = objects;
+
+        Ok(pull)
+    }
+
+    /**
+     * ETags of every object of the calendar, by href.
+     */
+    pub(crate) fn etags(&self) -> crate::Result<BTreeMap<String, String>> {
+        let response = self.query(
+            r#"
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <d:getetag />
+    </d:prop>
+    <c:filter>
+        <c:comp-filter name="VCALENDAR" />
+    </c:filter>
+</c:calendar-query>
+"#,
+        )?;
+
+        let etags = crate::Object::from_response(self, &response)
+            .into_iter()
+            .filter_map(|x| Some((x.url().to_string(), x.etag?)))
+            .collect();
+
+        Ok(etags)
+    }
+
+    /**
+     * Downloads `objects` with their ETag in one `calendar-multiget` report.
+     */
+    pub(crate) fn multiget(&self, objects: &[crate::Object]) -> crate::Result<Vec<crate::Object>> {
+        if objects.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let hrefs = objects
+            .iter()
+            .map(|x| format!("<d:href>{}</d:href>", crate::escape(crate::path(x.url()))))
+            .collect::<String>();
+
+        let body = format!(
+            r#"
+<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <d:getetag />
+        <c:calendar-data />
+    </d:prop>
+    {hrefs}
+</c:calendar-multiget>
+"#
+        );
+
+        let response = self.query(&body)?;
+
+        Ok(crate::Object::from_response(self, &response))
+    }
+
+    /**
+     * Components listed in the calendar `supported-calendar-component-set`,
+     * empty if the server didn't report it.
+     */
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    /**
+     * `REPORT` on the calendar, asking for the calendar data without the
+     * standard `VTIMEZONE`s when enabled, see
+     * [`crate::Client::set_timezones_by_reference`].
+     */
+    fn query(&self, body: &str) -> crate::Result<String> {
+        let mut headers = BTreeMap::new();
+
+        headers.insert("Depth", "1");
+
+        if self.timezones_by_reference {
+            headers.insert("CalDAV-Timezones", "F");
+        }
+
+        self.request("REPORT", &self.url, Some(body), Some(headers))
+    }
+
+    /**
+     * A calendar without `supported-calendar-component-set` accepts any
+     * component type (RFC 4791 §5.2.3).
+     */
+    pub fn supports(&self, component: &str) -> bool {
+        self.components.is_empty()
+            || self
+                .components
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(component))
+    }
+
+    fn component(&self, component: &str) -> crate::Result<crate::object::Iterator> {
+        if !self.supports(component) {
+            return Ok(crate::object::Iterator::from(Vec::new()));
+        }
+
+        let response = match self.request(Some(component)) {
+            Ok(response) => response,
+            Err(err) => {
+                return self.fallback(err, |snapshot| {
+                    snapshot.objects(self, component, None, None)
+                })
+            }
+        };
 
         Ok(crate::object::Iterator::from(
             self.to_vec(&response, "//d:response/d:href/text()"),
         ))
     }
 
+    /**
+     * The object at `href`, like the one of an [`crate::Alarm`]. Its data is
+     * downloaded on first use.
+     */
+    pub fn object(&self, href: &str) -> crate::Object {
+        let mut object = crate::Object::new(href, &BTreeMap::new());
+        object.set_auth(self.auth.clone());
+
+        object
+    }
+
+    /**
+     * Finds the event, task or journal with this UID. The `text-match` of
+     * the query is a substring search (RFC 4791 §9.7.5), the exact UID is
+     * checked on the returned data.
+     */
+    pub fn find_by_uid(&self, uid: &str) -> crate::Result<Option<crate::Object>> {
+        for component in ["VEVENT", "VTODO", "VJOURNAL"] {
+            if !self.supports(component) {
+                continue;
+            }
+
+            if let Some(object) = self.object_by_uid(component, uid)? {
+                return Ok(Some(object));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub(crate) fn object_by_uid(
+        &self,
+        component: &str,
+        uid: &str,
+    ) -> crate::Result<Option<crate::Object>> {
+        let body = format!(
+            r#"
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <d:getetag />
+        <c:calendar-data />
+    </d:prop>
+    <c:filter>
+        <c:comp-filter name="VCALENDAR">
+            <c:comp-filter name="{component}">
+                <c:prop-filter name="UID">
+                    <c:text-match collation="i;octet">{}</c:text-match>
+                </c:prop-filter>
+            </c:comp-filter>
+        </c:comp-filter>
+    </c:filter>
+</c:calendar-query>
+"#,
+            crate::escape(uid)
+        );
+
+        let response = self.query(&body)?;
+
+        let object = crate::Object::from_response(self, &response)
+            .into_iter()
+            .find(|x| {
+                x.data
+                    .as_deref()
+                    .map(|data| crate::ics::uid_of(data).as_deref() == Some(uid))
+                    .unwrap_or(true)
+            });
+
+        Ok(object)
+    }
+
+    /**
+     * Stores `meeting` with `principal` as organizer, a scheduling server
+     * then invites the attendees (RFC 6638 §3.2). The returned object
+     * contains the delivery status of every attendee.
+     */
+    pub fn create_meeting(
+        &self,
+        principal: &crate::Principal,
+        meeting: &crate::Meeting,
+    ) -> crate::Result<crate::Object> {
+        let organizer = principal
+            .addresses()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| crate::Error::Misc("No calendar user address found".to_string()))?;
+
+        let uid = crate::ics::uid();
+        let url = self.object_url(&uid);
+
+        self.put(url.as_str(), &meeting.to_ics(&uid, &organizer), None)?;
+
+        let mut object: crate::Object = crate::Children::new(url, &Default::default());
+        object.set_auth(self.auth());
+        object.fetch()?;
+
+        Ok(object)
+    }
+
+    /**
+     * Location of a new object named after its UID.
+     */
+    pub(crate) fn object_url(&self, uid: &str) -> String {
+        let name = uid
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "-_.@".contains(c) {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>();
+
+        format!("{}/{name}.ics", self.url.trim_end_matches('/'))
+    }
+
     fn request(&self, filter: Option<&str>) -> crate::Result<String> {
         let filter = if let Some(filter) = filter {
             format!("<c:comp-filter name=\"{filter}\" />")
@@ -54,7 +480,7 @@ impl Calendar {
 "#
         );
 
-        self.report(&self.url, &body)
+        self.query(&body)
     }
 
     pub fn search<Tz>(
@@ -66,25 +492,89 @@ impl Calendar {
         Tz: chrono::TimeZone,
         Tz::Offset: std::fmt::Display,
     {
-        let date_format = "%Y%m%dT%H%M%SZ";
+        self.search_component("VEVENT", start, end)
+    }
+
+    pub fn search_journals<Tz>(
+        &self,
+        start: Option<chrono::DateTime<Tz>>,
+        end: Option<chrono::DateTime<Tz>>,
+    ) -> crate::Result<crate::object::Iterator>
+    where
+        Tz: chrono::TimeZone,
+        Tz::Offset: std::fmt::Display,
+    {
+        self.search_component("VJOURNAL", start, end)
+    }
+
+    fn search_component<Tz>(
+        &self,
+        component: &str,
+        start: Option<chrono::DateTime<Tz>>,
+        end: Option<chrono::DateTime<Tz>>,
+    ) -> crate::Result<crate::object::Iterator>
+    where
+        Tz: chrono::TimeZone,
+        Tz::Offset: std::fmt::Display,
+    {
+        if !self.supports(component) {
+            return Ok(crate::object::Iterator::from(Vec::new()));
+        }
+
+        let start = start.map(|x| x.with_timezone(&chrono::Utc));
+        let end = end.map(|x| x.with_timezone(&chrono::Utc));
+
+        let response = match self.query(component, start, end, "<d:resourcetype />") {
+            Ok(response) => response,
+            Err(err) => {
+                return self.fallback(err, |snapshot| {
+                    snapshot.objects(self, component, start, end)
+                })
+            }
+        };
+
+        Ok(crate::object::Iterator::from(
+            self.to_vec(&response, "//d:response/d:href/text()"),
+        ))
+    }
+
+    /**
+     * Answers from the offline snapshot when the server can't be reached.
+     */
+    fn fallback<F>(&self, err: crate::Error, f: F) -> crate::Result<crate::object::Iterator>
+    where
+        F: FnOnce(&crate::Snapshot) -> crate::object::Iterator,
+    {
+        match &self.offline {
+            Some(snapshot) if err.is_offline() => Ok(f(snapshot)),
+            _ => Err(err),
+        }
+    }
 
+    fn query(
+        &self,
+        component: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: Option<chrono::DateTime<chrono::Utc>>,
+        prop: &str,
+    ) -> crate::Result<String> {
         let start = start
-            .map(|x| x.naive_utc().format(date_format).to_string())
+            .map(|x| crate::ics::format_date_time(&x))
             .unwrap_or_else(|| "-infinity".to_string());
 
         let end = end
-            .map(|x| x.naive_utc().format(date_format).to_string())
+            .map(|x| crate::ics::format_date_time(&x))
             .unwrap_or_else(|| "+infinity".to_string());
 
         let body = format!(
             r#"
 <c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
     <d:prop>
-        <d:resourcetype />
+        {prop}
     </d:prop>
     <c:filter>
         <c:comp-filter name="VCALENDAR">
-            <c:comp-filter name="VEVENT">
+            <c:comp-filter name="{component}">
                 <c:time-range start="{start}" end="{end}"/>
             </c:comp-filter>
         </c:comp-filter>
@@ -92,10 +582,429 @@ impl Calendar {
 </c:calendar-query>"#
         );
 
-        let response = self.report(&self.url, &body)?;
+        self.query(&body)
+    }
 
-        Ok(crate::object::Iterator::from(
-            self.to_vec(&response, "//d:response/d:href/text()"),
-        ))
+    /**
+     * The timezone service of this calendar, with its `calendar-timezone`.
+     * Floating times are in the calendar timezone, or in the `device` zone
+     * without one.
+     *
+     * Servers may only give the `calendar-timezone-id` (RFC 7809), it is
+     * then resolved from the IANA database or downloaded from `services`,
+     * see [`crate::Home::timezone_services`].
+     */
+    pub fn timezones(
+        &self,
+        device: crate::Zone,
+        services: &[String],
+    ) -> crate::Result<crate::Timezones> {
+        let response = self.propfind_self(
+            &self.url,
+            r#"
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <c:calendar-timezone />
+        <c:calendar-timezone-id />
+    </d:prop>
+</d:propfind>
+"#,
+        )?;
+
+        let mut timezones = crate::Timezones::new(device);
+        let mut tzid = Self::xml(&response, "//cal:calendar-timezone-id/text()")
+            .into_iter()
+            .next();
+
+        for data in Self::xml(&response, "//cal:calendar-timezone/text()") {
+            timezones.add(&data);
+
+            tzid = tzid.or_else(|| {
+                crate::ics::parse(&data)
+                    .iter()
+                    .flat_map(|x| x.children("VTIMEZONE"))
+                    .find_map(|x| x.value("TZID").map(str::to_string))
+            });
+        }
+
+        let zone = match tzid {
+            Some(tzid) => timezones.fetch_zone(&tzid, services)?,
+            None => None,
+        };
+
+        if let Some(zone) = zone {
+            timezones.set_calendar(zone);
+        }
+
+        Ok(timezones)
+    }
+
+    /**
+     * Alarms of the events and tasks triggering between `start` and `end`,
+     * sorted by trigger time.
+     *
+     * Recurrences are expanded up to a week after `end`: an alarm set more
+     * than a week before its occurrence isn't found.
+     */
+    pub fn alarms<Tz>(
+        &self,
+        start: chrono::DateTime<Tz>,
+        end: chrono::DateTime<Tz>,
+    ) -> crate::Result<Vec<crate::Alarm>>
+    where
+        Tz: chrono::TimeZone,
+    {
+        let start = start.with_timezone(&chrono::Utc);
+        let end = end.with_timezone(&chrono::Utc);
+        let mut alarms = Vec::new();
+
+        for component in ["VEVENT", "VTODO"] {
+            for object in self.expand(component, start, end + chrono::Duration::days(7))? {
+                if let Some(data) = &object.data {
+                    alarms.extend(crate::alarm::evaluate(object.url(), data, start, end));
+                }
+            }
+        }
+
+        Ok(crate::alarm::sort(alarms))
+    }
+
+    /**
+     * Busy time between `start` and `end` using a `free-busy-query` report.
+     * When the server doesn't implement it, the result is computed from the
+     * calendar events.
+     */
+    pub fn free_busy<Tz>(
+        &self,
+        start: chrono::DateTime<Tz>,
+        end: chrono::DateTime<Tz>,
+    ) -> crate::Result<Vec<crate::FreeBusy>>
+    where
+        Tz: chrono::TimeZone,
+    {
+        let start = start.with_timezone(&chrono::Utc);
+        let end = end.with_timezone(&chrono::Utc);
+
+        let body = format!(
+            r#"
+<c:free-busy-query xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <c:time-range start="{}" end="{}"/>
+</c:free-busy-query>"#,
+            crate::ics::format_date_time(&start),
+            crate::ics::format_date_time(&end),
+        );
+
+        match self.query(&body) {
+            Ok(response) => Ok(crate::freebusy::parse(&response)),
+            Err(err) if err.is_unsupported() => self.free_busy_from_events(start, end),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn free_busy_from_events(
+        &self,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<crate::FreeBusy>> {
+        let data = self
+            .expand("VEVENT", start, end)?
+            .into_iter()
+            .filter_map(|x| x.data)
+            .collect::<Vec<_>>();
+
+        Ok(crate::freebusy::from_events(&data, start, end))
+    }
+
+    /**
+     * Objects with a `component` between `start` and `end`, their data has
+     * the recurrences expanded by the server (RFC 4791 §9.6.5).
+     */
+    pub(crate) fn expand(
+        &self,
+        component: &str,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<crate::Object>> {
+        if !self.supports(component) {
+            return Ok(Vec::new());
+        }
+
+        let expand = format!(
+            r#"<c:calendar-data>
+            <c:expand start="{}" end="{}"/>
+        </c:calendar-data>"#,
+            crate::ics::format_date_time(&start),
+            crate::ics::format_date_time(&end),
+        );
+
+        let response = self.query(component, Some(start), Some(end), &expand)?;
+
+        Ok(crate::Object::from_response(self, &response))
+    }
+
+    /**
+     * Changes the given properties with `PROPPATCH`. The update is atomic
+     * (RFC 4918 §9.2): if a property is rejected, none is changed and every
+     * failure is returned.
+     */
+    pub fn update_properties(
+        &mut self,
+        properties: &CalendarProperties,
+    ) -> crate::Result<Vec<PropertyError>> {
+        let mut props = Vec::new();
+
+        if let Some(name) = &properties.name {
+            props.push(("d:displayname", crate::escape(name)));
+        }
+
+        if let Some(color) = &properties.color {
+            props.push(("x1:calendar-color", crate::escape(color)));
+        }
+
+        if let Some(description) = &properties.description {
+            props.push(("cal:calendar-description", crate::escape(description)));
+        }
+
+        if let Some(order) = properties.order {
+            props.push(("x1:calendar-order", order.to_string()));
+        }
+
+        if props.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let prop = props
+            .iter()
+            .map(|(name, value)| format!("<{name}>{value}</{name}>"))
+            .collect::<String>();
+
+        let body = format!(
+            r#"
+<d:propertyupdate xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav" xmlns:x1="http://apple.com/ns/ical/">
+  <d:set>
+    <d:prop>
+      {prop}
+    </d:prop>
+  </d:set>
+</d:propertyupdate>
+"#
+        );
+
+        let response = self.proppatch(&self.url, &body)?;
+        let mut errors = Vec::new();
+
+        for (name, _) in props {
+            let status = Self::xml(
+                &response,
+                &format!("//d:propstat[d:prop/{name}]/d:status/text()"),
+            )
+            .first()
+            .and_then(|x| x.split_whitespace().nth(1).and_then(|x| x.parse().ok()))
+            .unwrap_or(200);
+
+            if !(200..300).contains(&status) {
+                errors.push(PropertyError {
+                    name: name
+                        .split_once(':')
+                        .map(|x| x.1)
+                        .unwrap_or(name)
+                        .to_string(),
+                    status,
+                });
+            }
+        }
+
+        if errors.is_empty() && properties.color.is_some() {
+            self.color = properties.color.clone();
+        }
+
+        Ok(errors)
+    }
+
+    /**
+     * Removes the calendar collection and all its objects.
+     */
+    pub fn delete(&self) -> crate::Result {
+        Requestable::delete(self, self.url.as_str(), None)
+    }
+
+    /**
+     * What the current user is allowed to do on this calendar.
+     */
+    pub fn privileges(&self) -> crate::Result<crate::Privileges> {
+        let response = self.propfind_self(
+            &self.url,
+            r#"
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+     <d:current-user-privilege-set />
+  </d:prop>
+</d:propfind>
+"#,
+        )?;
+
+        Ok(crate::Privileges(crate::Privilege::from_response::<Self>(
+            &response,
+            "//d:current-user-privilege-set/d:privilege",
+        )))
+    }
+
+    /**
+     * The access control list, usually only readable by the owner or the
+     * administrators.
+     */
+    pub fn acl(&self) -> crate::Result<Vec<crate::Ace>> {
+        let response = self.propfind_self(
+            &self.url,
+            r#"
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+     <d:acl />
+  </d:prop>
+</d:propfind>
+"#,
+        )?;
+
+        let count = Self::xml(&response, "//d:acl/d:ace").len();
+        let mut aces = Vec::new();
+
+        for i in 1..=count {
+            let ace = format!("//d:acl/d:ace[{i}]");
+            let exists = |xpath: &str| !Self::xml(&response, &format!("{ace}/{xpath}")).is_empty();
+
+            let principal = if let Some(href) =
+                Self::xml(&response, &format!("{ace}/d:principal/d:href/text()")).first()
+            {
+                crate::AcePrincipal::Href(href.clone())
+            } else if exists("d:principal/d:all") {
+                crate::AcePrincipal::All
+            } else if exists("d:principal/d:authenticated") {
+                crate::AcePrincipal::Authenticated
+            } else if exists("d:principal/d:unauthenticated") {
+                crate::AcePrincipal::Unauthenticated
+            } else if exists("d:principal/d:self") {
+                crate::AcePrincipal::Itself
+            } else {
+                crate::AcePrincipal::Property
+            };
+
+            aces.push(crate::Ace {
+                principal,
+                invert: exists("d:invert"),
+                grant: crate::Privilege::from_response::<Self>(
+                    &response,
+                    &format!("{ace}/d:grant/d:privilege"),
+                ),
+                deny: crate::Privilege::from_response::<Self>(
+                    &response,
+                    &format!("{ace}/d:deny/d:privilege"),
+                ),
+                protected: exists("d:protected"),
+                inherited: Self::xml(&response, &format!("{ace}/d:inherited/d:href/text()"))
+                    .into_iter()
+                    .next(),
+            });
+        }
+
+        Ok(aces)
+    }
+
+    /**
+     * Shares the calendar with `address` using the CalendarServer sharing
+     * extension, the sharee receives an invitation.
+     */
+    pub fn share(
+        &self,
+        address: &str,
+        access: crate::Access,
+        summary: Option<&str>,
+    ) -> crate::Result {
+        let summary = summary
+            .map(|x| format!("<cs:summary>{}</cs:summary>", crate::escape(x)))
+            .unwrap_or_default();
+
+        let body = format!(
+            r#"
+<cs:share xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+  <cs:set>
+    <d:href>{}</d:href>
+    {summary}
+    {}
+  </cs:set>
+</cs:share>
+"#,
+            crate::escape(&crate::ics::cal_address(address)),
+            access.element(),
+        );
+
+        self.post(&self.url, &body, "application/xml; charset=utf-8")?;
+
+        Ok(())
+    }
+
+    pub fn unshare(&self, address: &str) -> crate::Result {
+        let body = format!(
+            r#"
+<cs:share xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+  <cs:remove>
+    <d:href>{}</d:href>
+  </cs:remove>
+</cs:share>
+"#,
+            crate::escape(&crate::ics::cal_address(address)),
+        );
+
+        self.post(&self.url, &body, "application/xml; charset=utf-8")?;
+
+        Ok(())
+    }
+
+    pub fn sharees(&self) -> crate::Result<Vec<crate::Sharee>> {
+        let response = self.propfind_self(
+            &self.url,
+            r#"
+<d:propfind xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+  <d:prop>
+     <cs:invite />
+  </d:prop>
+</d:propfind>
+"#,
+        )?;
+
+        let addresses = Self::xml(&response, "//cs:invite/cs:user/d:href/text()");
+
+        let sharees = addresses
+            .into_iter()
+            .enumerate()
+            .map(|(i, address)| {
+                let xpath = format!("//cs:invite/cs:user[{}]", i + 1);
+
+                crate::Sharee {
+                    address,
+                    name: Self::xml(&response, &format!("{xpath}/cs:common-name/text()"))
+                        .into_iter()
+                        .next(),
+                    access: crate::Access::from_response::<Self>(&response, &xpath),
+                    status: crate::InviteStatus::from_response::<Self>(&response, &xpath),
+                }
+            })
+            .collect();
+
+        Ok(sharees)
+    }
+
+    pub fn reports(&self) -> crate::Result<Vec<crate::Report>> {
+        let response = self.propfind_self(
+            &self.url,
+            r#"
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+     <d:supported-report-set />
+  </d:prop>
+</d:propfind>
+"#,
+        )?;
+
+        Ok(crate::Report::from_response::<Self>(&response))
     }
 }
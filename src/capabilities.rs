@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Report {
+    CalendarQuery,
+    CalendarMultiget,
+    FreeBusyQuery,
+    SyncCollection,
+    ExpandProperty,
+    PrincipalMatch,
+    PrincipalPropertySearch,
+    AclPrincipalPropSet,
+}
+
+impl Report {
+    const ALL: [Self; 8] = [
+        Self::CalendarQuery,
+        Self::CalendarMultiget,
+        Self::FreeBusyQuery,
+        Self::SyncCollection,
+        Self::ExpandProperty,
+        Self::PrincipalMatch,
+        Self::PrincipalPropertySearch,
+        Self::AclPrincipalPropSet,
+    ];
+
+    fn element(&self) -> &'static str {
+        match self {
+            Self::CalendarQuery => "cal:calendar-query",
+            Self::CalendarMultiget => "cal:calendar-multiget",
+            Self::FreeBusyQuery => "cal:free-busy-query",
+            Self::SyncCollection => "d:sync-collection",
+            Self::ExpandProperty => "d:expand-property",
+            Self::PrincipalMatch => "d:principal-match",
+            Self::PrincipalPropertySearch => "d:principal-property-search",
+            Self::AclPrincipalPropSet => "d:acl-principal-prop-set",
+        }
+    }
+
+    pub(crate) fn from_response<X>(response: &str) -> Vec<Self>
+    where
+        X: crate::Xmlable,
+    {
+        Self::ALL
+            .into_iter()
+            .filter(|x| {
+                let xpath = format!(
+                    "//d:supported-report-set/d:supported-report/d:report/{}",
+                    x.element()
+                );
+
+                !X::xml(response, &xpath).is_empty()
+            })
+            .collect()
+    }
+}
+
+/**
+ * What the server supports, from the `DAV` and `Allow` headers of an
+ * `OPTIONS` request and the `supported-report-set` of every calendar.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Capabilities {
+    pub dav: Vec<String>,
+    pub methods: Vec<String>,
+    /**
+     * Reports of every calendar, by calendar URL.
+     */
+    pub reports: BTreeMap<String, Vec<Report>>,
+}
+
+impl Capabilities {
+    pub(crate) fn from_response(response: &crate::Response) -> Self {
+        let list = |name: &str| -> Vec<String> {
+            response
+                .header(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect()
+        };
+
+        Self {
+            dav: list("DAV"),
+            methods: list("Allow"),
+            reports: BTreeMap::new(),
+        }
+    }
+
+    /**
+     * Checks a compliance class of the `DAV` header, like `calendar-access`.
+     */
+    pub fn has(&self, class: &str) -> bool {
+        self.dav.iter().any(|x| x.eq_ignore_ascii_case(class))
+    }
+
+    pub fn calendar_access(&self) -> bool {
+        self.has("calendar-access")
+    }
+
+    pub fn auto_schedule(&self) -> bool {
+        self.has("calendar-auto-schedule")
+    }
+
+    pub fn extended_mkcol(&self) -> bool {
+        self.has("extended-mkcol")
+    }
+
+    pub fn calendar_proxy(&self) -> bool {
+        self.has("calendar-proxy")
+    }
+
+    pub fn sharing(&self) -> bool {
+        self.has("calendarserver-sharing")
+    }
+
+    /**
+     * Timezones by reference (RFC 7809), see
+     * [`crate::Client::set_timezones_by_reference`].
+     */
+    pub fn timezones_by_reference(&self) -> bool {
+        self.has("calendar-no-timezone")
+    }
+
+    pub fn allows(&self, method: &str) -> bool {
+        self.methods.iter().any(|x| x.eq_ignore_ascii_case(method))
+    }
+
+    /**
+     * Checks if `calendar` supports `report`.
+     */
+    pub fn supports(&self, calendar: &crate::Calendar, report: Report) -> bool {
+        self.reports
+            .get(crate::Xmlable::url(calendar))
+            .map(|x| x.contains(&report))
+            .unwrap_or(false)
+    }
+}
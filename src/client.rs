@@ -1,5 +1,6 @@
 use crate::Children;
 use crate::Requestable;
+use crate::Xmlable;
 use std::collections::BTreeMap;
 use std::convert::Into;
 
@@ -7,6 +8,8 @@ use std::convert::Into;
 pub struct Client {
     url: String,
     auth: Option<crate::Authorization>,
+    offline: Option<std::sync::Arc<crate::Snapshot>>,
+    timezones_by_reference: bool,
 }
 
 impl Client {
@@ -17,6 +20,8 @@ impl Client {
         Self {
             url: url.into(),
             auth: None,
+            offline: None,
+            timezones_by_reference: false,
         }
     }
 
@@ -32,26 +37,228 @@ impl Client {
 "#,
         )?;
 
-        Ok(self.to_vec(&response, "//d:current-user-principal/d:href/text()"))
-    }
+        let mut principals: Vec<crate::Principal> =
+            self.to_vec(&response, "//d:current-user-principal/d:href/text()");
+        principals.dedup_by(|a, b| a.url() == b.url());
 
-    fn principal(&self) -> crate::Result<crate::Principal> {
-        let mut principals = self.principals()?;
+        Ok(principals)
+    }
 
-        Ok(principals.remove(0))
+    /**
+     * Calendar homes of every principal, a principal may have several of
+     * them (delegation, proxy).
+     */
+    pub fn homes(&self) -> crate::Result<Vec<crate::Home>> {
+        self.homes_of(&self.principals()?)
     }
 
-    fn home(&self) -> crate::Result<crate::Home> {
-        self.principal()?.home()
+    fn homes_of(&self, principals: &[crate::Principal]) -> crate::Result<Vec<crate::Home>> {
+        if principals.is_empty() {
+            return Err(crate::Error::Misc(format!(
+                "No principal found at {}",
+                self.url
+            )));
+        }
+
+        let mut homes: Vec<crate::Home> = Vec::new();
+
+        for principal in principals {
+            for home in principal.homes()? {
+                if !homes.iter().any(|x| x.url() == home.url()) {
+                    homes.push(home);
+                }
+            }
+        }
+
+        if homes.is_empty() {
+            return Err(crate::Error::Misc(format!("No home found at {}", self.url)));
+        }
+
+        Ok(homes)
     }
 
+    /**
+     * Calendars of every home. When two calendars have the same name, the
+     * last ones are suffixed by their URL.
+     *
+     * If the server can't be reached, the calendars of the offline snapshot
+     * are returned instead, see [`Client::set_offline`].
+     */
     pub fn calendars(&self) -> crate::Result<BTreeMap<String, crate::Calendar>> {
-        let home = self.home()?;
+        let mut calendars = match self.remote_calendars() {
+            Ok(calendars) => calendars,
+            Err(err) => match &self.offline {
+                Some(snapshot) if err.is_offline() => snapshot.calendars(self.auth.clone()),
+                _ => return Err(err),
+            },
+        };
+
+        for calendar in calendars.values_mut() {
+            calendar.offline = self.offline.clone();
+            calendar.timezones_by_reference = self.timezones_by_reference;
+        }
+
+        Ok(calendars)
+    }
+
+    fn remote_calendars(&self) -> crate::Result<BTreeMap<String, crate::Calendar>> {
+        calendars(&self.homes()?)
+    }
 
-        home.calendars()
+    /**
+     * Upcoming events of every calendar from `from`, sorted by start, with
+     * their recurrences expanded by the server. Times are in the timezone of
+     * `from`.
+     */
+    pub fn agenda<Tz>(
+        &self,
+        from: chrono::DateTime<Tz>,
+        limit: crate::AgendaLimit,
+    ) -> crate::Result<Vec<crate::Occurrence<Tz>>>
+    where
+        Tz: chrono::TimeZone,
+    {
+        crate::agenda::agenda(&self.calendars()?, from, limit)
+    }
+
+    /**
+     * Alarms of every calendar triggering between `start` and `end`, sorted
+     * by trigger time, see [`crate::Calendar::alarms`].
+     */
+    pub fn alarms<Tz>(
+        &self,
+        start: chrono::DateTime<Tz>,
+        end: chrono::DateTime<Tz>,
+    ) -> crate::Result<Vec<crate::Alarm>>
+    where
+        Tz: chrono::TimeZone,
+    {
+        let mut alarms = Vec::new();
+
+        for calendar in self.calendars()?.values() {
+            alarms.append(&mut calendar.alarms(start.clone(), end.clone())?);
+        }
+
+        Ok(crate::alarm::sort(alarms))
+    }
+
+    /**
+     * Detects the server features, the reports are listed by calendar URL.
+     */
+    pub fn capabilities(&self) -> crate::Result<crate::Capabilities> {
+        let response = self.options(&self.url)?;
+        let mut capabilities = crate::Capabilities::from_response(&response);
+
+        for calendar in self.calendars()?.values() {
+            capabilities
+                .reports
+                .insert(calendar.url().to_string(), calendar.reports()?);
+        }
+
+        Ok(capabilities)
+    }
+
+    /**
+     * Discovers the homes, as [`Client::homes`], then lists their calendars
+     * with the ETags of their objects.
+     */
+    pub fn state(&self) -> crate::Result<crate::State> {
+        let mut state = self.discover()?;
+        state.refresh()?;
+
+        Ok(state)
+    }
+
+    /**
+     * The principals and their homes, without any calendar.
+     */
+    fn discover(&self) -> crate::Result<crate::State> {
+        let principals = self.principals()?;
+        let homes = self.homes_of(&principals)?;
+
+        Ok(crate::State::new(&principals[0], &homes))
+    }
+
+    /**
+     * Restores a state saved with [`crate::State::to_bytes`] without any
+     * request, [`crate::State::refresh`] then costs a single request when
+     * nothing changed.
+     */
+    pub fn resume(&self, bytes: &[u8]) -> crate::Result<crate::State> {
+        let mut state = crate::State::from_bytes(bytes)?;
+        state.set_auth(self.auth.clone());
+
+        Ok(state)
+    }
+
+    /**
+     * Downloads every calendar of every home and saves it in `storage`, to be
+     * used with [`Client::set_offline`] when the network is down. The
+     * calendars are named as by [`Client::calendars`].
+     */
+    pub fn snapshot<S>(&self, storage: &mut S) -> crate::Result<crate::Snapshot>
+    where
+        S: crate::Storage,
+    {
+        // The objects are listed once, by the snapshot
+        let mut state = self.discover()?;
+        state.list()?;
+
+        crate::Snapshot::take(state, storage)
+    }
+
+    /**
+     * Answers [`Client::calendars`], [`crate::Calendar::events`] and
+     * [`crate::Calendar::search`] from `snapshot` when the server can't be
+     * reached. Their results are then marked with the snapshot time.
+     */
+    pub fn set_offline(&mut self, snapshot: Option<crate::Snapshot>) {
+        self.offline = snapshot.map(std::sync::Arc::new);
+    }
+
+    /**
+     * Asks for the calendar data of [`Client::calendars`] without the
+     * standard `VTIMEZONE`s (RFC 7809), for servers advertising it, see
+     * [`crate::Capabilities::timezones_by_reference`]. Their `TZID`s must
+     * then be resolved with [`crate::Timezones::fetch`].
+     */
+    pub fn set_timezones_by_reference(&mut self, enabled: bool) {
+        self.timezones_by_reference = enabled;
+    }
+
+    pub fn delegations(&self) -> crate::Result<Vec<crate::Delegation>> {
+        let mut delegations = Vec::new();
+
+        for principal in self.principals()? {
+            delegations.append(&mut principal.delegations()?);
+        }
+
+        Ok(delegations)
     }
 
     pub fn set_auth(&mut self, auth: Option<crate::Authorization>) {
         crate::Requestable::set_auth(self, auth)
     }
 }
+
+/**
+ * Calendars of `homes`. When two calendars have the same name, the last ones
+ * are suffixed by their URL.
+ */
+pub(crate) fn calendars(homes: &[crate::Home]) -> crate::Result<BTreeMap<String, crate::Calendar>> {
+    let mut calendars = BTreeMap::new();
+
+    for home in homes {
+        for (name, calendar) in home.calendars()? {
+            let name = if calendars.contains_key(&name) {
+                format!("{name} ({})", calendar.url())
+            } else {
+                name
+            };
+
+            calendars.insert(name, calendar);
+        }
+    }
+
+    Ok(calendars)
+}
@@ -0,0 +1,154 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FreeBusyType {
+    Free,
+    Busy,
+    BusyUnavailable,
+    BusyTentative,
+}
+
+impl FreeBusyType {
+    /**
+     * Unknown types are handled as `BUSY` (RFC 5545 §3.2.9).
+     */
+    fn from_param(param: Option<&str>) -> Self {
+        match param.map(str::to_uppercase).as_deref() {
+            Some("FREE") => Self::Free,
+            Some("BUSY-UNAVAILABLE") => Self::BusyUnavailable,
+            Some("BUSY-TENTATIVE") => Self::BusyTentative,
+            _ => Self::Busy,
+        }
+    }
+}
+
+impl std::fmt::Display for FreeBusyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            Self::Free => "FREE",
+            Self::Busy => "BUSY",
+            Self::BusyUnavailable => "BUSY-UNAVAILABLE",
+            Self::BusyTentative => "BUSY-TENTATIVE",
+        };
+
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FreeBusy {
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub end: chrono::DateTime<chrono::Utc>,
+    pub fbtype: FreeBusyType,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleResponse {
+    pub recipient: String,
+    pub status: String,
+    pub periods: Vec<FreeBusy>,
+}
+
+impl ScheduleResponse {
+    /**
+     * `status` is an iTIP request status (RFC 5545 §3.8.8.3), `2.x` codes
+     * are successes.
+     */
+    pub fn is_success(&self) -> bool {
+        self.status.starts_with('2')
+    }
+}
+
+/**
+ * Extracts the `FREEBUSY` periods of every `VFREEBUSY` component.
+ */
+pub(crate) fn parse(data: &str) -> Vec<FreeBusy> {
+    let mut periods = Vec::new();
+
+    for calendar in crate::ics::parse(data) {
+        for vfreebusy in calendar.children("VFREEBUSY") {
+            for property in vfreebusy.properties("FREEBUSY") {
+                let fbtype = FreeBusyType::from_param(property.param("FBTYPE"));
+
+                for value in property.value.split(',') {
+                    if let Some((start, end)) = crate::ics::parse_period(value) {
+                        periods.push(FreeBusy { start, end, fbtype });
+                    }
+                }
+            }
+        }
+    }
+
+    normalize(periods)
+}
+
+/**
+ * Computes the busy time of the events in `data` like a server does for a
+ * `free-busy-query` (RFC 4791 §7.10): transparent and cancelled events are
+ * ignored, tentative ones are `BUSY-TENTATIVE`.
+ */
+pub(crate) fn from_events(
+    data: &[String],
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+) -> Vec<FreeBusy> {
+    let mut periods = Vec::new();
+
+    for calendar in data.iter().flat_map(|x| crate::ics::parse(x)) {
+        let timezones = crate::Timezones::embedded(&calendar);
+
+        for event in calendar.children("VEVENT") {
+            let transparent = event
+                .value("TRANSP")
+                .map(|x| x.eq_ignore_ascii_case("TRANSPARENT"))
+                .unwrap_or(false);
+
+            if transparent {
+                continue;
+            }
+
+            let fbtype = match event.value("STATUS").map(str::to_uppercase).as_deref() {
+                Some("CANCELLED") => continue,
+                Some("TENTATIVE") => FreeBusyType::BusyTentative,
+                _ => FreeBusyType::Busy,
+            };
+
+            let Some((event_start, event_end)) = timezones.component_bounds(event) else {
+                continue;
+            };
+
+            let event_start = event_start.max(start);
+            let event_end = event_end.min(end);
+
+            if event_start < event_end {
+                periods.push(FreeBusy {
+                    start: event_start,
+                    end: event_end,
+                    fbtype,
+                });
+            }
+        }
+    }
+
+    normalize(periods)
+}
+
+/**
+ * Sorts periods and coalesces overlapping ones of the same type.
+ */
+fn normalize(mut periods: Vec<FreeBusy>) -> Vec<FreeBusy> {
+    periods.sort_by(|a, b| (a.fbtype, a.start).cmp(&(b.fbtype, b.start)));
+
+    let mut merged: Vec<FreeBusy> = Vec::new();
+
+    for period in periods {
+        match merged.last_mut() {
+            Some(last) if last.fbtype == period.fbtype && period.start <= last.end => {
+                last.end = last.end.max(period.end);
+            }
+            _ => merged.push(period),
+        }
+    }
+
+    merged.sort_by(|a, b| a.start.cmp(&b.start));
+
+    merged
+}
@@ -1,5 +1,6 @@
 use crate::Children;
 use crate::Requestable;
+use crate::Xmlable;
 use std::collections::BTreeMap;
 
 #[derive(Clone, Debug, Default, crate::Object)]
@@ -16,20 +17,183 @@ impl Home {
      <d:resourcetype />
      <d:displayname />
      <cs:getctag />
+     <d:sync-token />
      <c:supported-calendar-component-set />
      <x1:calendar-color />
+     <c:calendar-timezone-id />
   </d:prop>
 </d:propfind>
 "#)?;
 
-        Ok(self.to_map(
+        let mut calendars: BTreeMap<String, crate::Calendar> = self.to_map(
             &response,
             "//d:response//d:displayname/text()",
             "//d:displayname [text() = '{}']/../../../d:href/text()",
-            vec![(
-                "color",
-                "//d:displayname [text() = '{}']/../x1:calendar-color/text()",
-            )],
-        ))
+            vec![
+                (
+                    "color",
+                    "//d:displayname [text() = '{}']/../x1:calendar-color/text()",
+                ),
+                (
+                    "ctag",
+                    "//d:displayname [text() = '{}']/../cs:getctag/text()",
+                ),
+                (
+                    "sync_token",
+                    "//d:displayname [text() = '{}']/../d:sync-token/text()",
+                ),
+                (
+                    "timezone_id",
+                    "//d:displayname [text() = '{}']/../cal:calendar-timezone-id/text()",
+                ),
+            ],
+        );
+
+        for (name, calendar) in calendars.iter_mut() {
+            calendar.components = Self::xml(
+                &response,
+                &format!("//d:displayname [text() = '{name}']/../cal:supported-calendar-component-set/cal:comp/@name"),
+            );
+        }
+
+        Ok(calendars)
+    }
+
+    /**
+     * Timezone distribution services (RFC 7808) advertised by the server to
+     * resolve the `TZID`s of calendar data without `VTIMEZONE` (RFC 7809
+     * §5.1).
+     */
+    pub fn timezone_services(&self) -> crate::Result<Vec<String>> {
+        let response = self.propfind_self(
+            &self.url,
+            r#"
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <c:timezone-service-set />
+    </d:prop>
+</d:propfind>
+"#,
+        )?;
+
+        let services = Self::xml(&response, "//cal:timezone-service-set/d:href/text()")
+            .into_iter()
+            .map(|x| {
+                if x.starts_with('/') {
+                    self.append_host(x)
+                } else {
+                    x
+                }
+            })
+            .collect();
+
+        Ok(services)
+    }
+
+    /**
+     * Creates a calendar collection named `slug` with `MKCALENDAR`, or with
+     * an extended `MKCOL` (RFC 5689) for servers without it. `timezone` is a
+     * `VCALENDAR` containing a single `VTIMEZONE`.
+     */
+    pub fn create_calendar(
+        &self,
+        slug: &str,
+        name: &str,
+        color: Option<&str>,
+        description: Option<&str>,
+        components: &[&str],
+        timezone: Option<&str>,
+    ) -> crate::Result<crate::Calendar> {
+        let url = format!("{}/{slug}/", self.url.trim_end_matches('/'));
+
+        let mut prop = format!("<d:displayname>{}</d:displayname>", crate::escape(name));
+
+        if let Some(color) = color {
+            prop.push_str(&format!(
+                "<x1:calendar-color>{}</x1:calendar-color>",
+                crate::escape(color)
+            ));
+        }
+
+        if let Some(description) = description {
+            prop.push_str(&format!(
+                "<c:calendar-description>{}</c:calendar-description>",
+                crate::escape(description)
+            ));
+        }
+
+        if !components.is_empty() {
+            let comps = components
+                .iter()
+                .map(|x| format!("<c:comp name=\"{}\" />", crate::escape(x)))
+                .collect::<String>();
+
+            prop.push_str(&format!(
+                "<c:supported-calendar-component-set>{comps}</c:supported-calendar-component-set>"
+            ));
+        }
+
+        if let Some(timezone) = timezone {
+            prop.push_str(&format!(
+                "<c:calendar-timezone>{}</c:calendar-timezone>",
+                crate::escape(timezone)
+            ));
+        }
+
+        let mut headers = BTreeMap::new();
+        headers.insert("Content-Type", "application/xml; charset=utf-8");
+
+        let body = format!(
+            r#"
+<c:mkcalendar xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:x1="http://apple.com/ns/ical/">
+  <d:set>
+    <d:prop>
+      {prop}
+    </d:prop>
+  </d:set>
+</c:mkcalendar>
+"#
+        );
+
+        match self.request(
+            "MKCALENDAR",
+            url.as_str(),
+            Some(&body),
+            Some(headers.clone()),
+        ) {
+            Ok(_) => (),
+            Err(err) if err.is_unsupported() => {
+                let body = format!(
+                    r#"
+<d:mkcol xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:x1="http://apple.com/ns/ical/">
+  <d:set>
+    <d:prop>
+      <d:resourcetype>
+        <d:collection />
+        <c:calendar />
+      </d:resourcetype>
+      {prop}
+    </d:prop>
+  </d:set>
+</d:mkcol>
+"#
+                );
+
+                self.request("MKCOL", url.as_str(), Some(&body), Some(headers))?;
+            }
+            Err(err) => return Err(err),
+        }
+
+        let mut params = BTreeMap::new();
+
+        if let Some(color) = color {
+            params.insert("color".to_string(), color.to_string());
+        }
+
+        let mut calendar = crate::Calendar::new(url, &params);
+        calendar.set_auth(self.auth());
+        calendar.components = components.iter().map(|x| x.to_uppercase()).collect();
+
+        Ok(calendar)
     }
 }
@@ -0,0 +1,487 @@
+/*!
+ * A minimal iCalendar reader and writer, to rewrite lines of the calendar
+ * data (`PARTSTAT`, `ACKNOWLEDGED`, …) without losing what [`ikal`] doesn't
+ * model. Calendar data is read with `ikal`, this module keeps its semantics:
+ * date-times with a `TZID` are resolved by [`crate::Timezones`] from the
+ * embedded `VTIMEZONE`s, not here.
+ */
+
+use chrono::TimeZone;
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn is_date(&self) -> bool {
+        self.param("VALUE")
+            .map(|x| x.eq_ignore_ascii_case("DATE"))
+            .unwrap_or_else(|| !self.value.contains('T'))
+    }
+}
+
+impl std::fmt::Display for Property {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.name)?;
+
+        for (key, value) in &self.params {
+            if value.contains([':', ';', ',']) {
+                write!(f, ";{key}=\"{value}\"")?;
+            } else {
+                write!(f, ";{key}={value}")?;
+            }
+        }
+
+        write!(f, ":{}", self.value)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+impl Component {
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|x| x.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn properties<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl std::iter::Iterator<Item = &'a Property> {
+        self.properties
+            .iter()
+            .filter(move |x| x.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.property(name).map(|x| x.value.as_str())
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl std::iter::Iterator<Item = &'a Component> {
+        self.components
+            .iter()
+            .filter(move |x| x.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl std::fmt::Display for Component {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "BEGIN:{}\r\n", self.name)?;
+
+        for property in &self.properties {
+            f.write_str(&fold(&property.to_string()))?;
+        }
+
+        for component in &self.components {
+            write!(f, "{component}")?;
+        }
+
+        write!(f, "END:{}\r\n", self.name)
+    }
+}
+
+impl Component {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, name: &str, params: &[(&str, &str)], value: &str) {
+        self.properties.push(Property {
+            name: name.to_string(),
+            params: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            value: value.to_string(),
+        });
+    }
+
+    /**
+     * A `VCALENDAR` wrapping `component`.
+     */
+    pub fn calendar(component: Component) -> Self {
+        let mut calendar = Self::new("VCALENDAR");
+        calendar.push("VERSION", &[], "2.0");
+        calendar.push("PRODID", &[], "-//kaldav//EN");
+        calendar.components.push(component);
+
+        calendar
+    }
+}
+
+/**
+ * Escapes a `TEXT` value (RFC 5545 §3.3.11).
+ */
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+pub(crate) fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+/**
+ * Parses an iCalendar stream into its top level components, usually a single
+ * `VCALENDAR`.
+ */
+pub(crate) fn parse(text: &str) -> Vec<Component> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut components = Vec::new();
+
+    for line in unfold(text) {
+        let Some(property) = parse_line(&line) else {
+            continue;
+        };
+
+        if property.name.eq_ignore_ascii_case("BEGIN") {
+            stack.push(Component {
+                name: property.value.to_uppercase(),
+                ..Default::default()
+            });
+        } else if property.name.eq_ignore_ascii_case("END") {
+            let Some(component) = stack.pop() else {
+                continue;
+            };
+
+            match stack.last_mut() {
+                Some(parent) => parent.components.push(component),
+                None => components.push(component),
+            }
+        } else if let Some(current) = stack.last_mut() {
+            current.properties.push(property);
+        }
+    }
+
+    components
+}
+
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        if let Some(continuation) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(continuation);
+                continue;
+            }
+        }
+
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    lines
+}
+
+/**
+ * Rewrites the content lines of `text`: `f` may change a property or remove
+ * it by returning `false`.
+ */
+pub(crate) fn rewrite<F>(text: &str, mut f: F) -> String
+where
+    F: FnMut(&mut Property) -> bool,
+{
+    let mut output = String::new();
+
+    for line in unfold(text) {
+        let line = match parse_line(&line) {
+            Some(mut property) => {
+                if !f(&mut property) {
+                    continue;
+                }
+
+                property.to_string()
+            }
+            None => line,
+        };
+
+        output.push_str(&fold(&line));
+    }
+
+    output
+}
+
+/**
+ * Splits a content line in chunks of 75 octets at most (RFC 5545 §3.1).
+ */
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+
+        folded.push(c);
+        len += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+
+    folded
+}
+
+/**
+ * Removes the `METHOD` property: scheduling messages can't be stored as is in
+ * a calendar (RFC 4791 §4.1).
+ */
+pub(crate) fn without_method(text: &str) -> String {
+    rewrite(text, |property| property.name != "METHOD")
+}
+
+/**
+ * Sets the participation status of the attendees matching one of
+ * `addresses`.
+ */
+pub(crate) fn set_partstat(text: &str, addresses: &[String], partstat: &str) -> String {
+    rewrite(text, |property| {
+        let matches = addresses
+            .iter()
+            .any(|x| x.eq_ignore_ascii_case(&property.value));
+
+        if property.name == "ATTENDEE" && matches {
+            property
+                .params
+                .retain(|(key, _)| key != "PARTSTAT" && key != "RSVP");
+            property
+                .params
+                .push(("PARTSTAT".to_string(), partstat.to_string()));
+        }
+
+        true
+    })
+}
+
+fn parse_line(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut separators = Vec::new();
+    let mut value_start = None;
+
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => separators.push(i),
+            ':' if !in_quotes => {
+                value_start = Some(i);
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    let value_start = value_start?;
+    let mut bounds = separators;
+    bounds.push(value_start);
+
+    let name = line[..bounds[0]].trim().to_uppercase();
+    let mut params = Vec::new();
+
+    for window in bounds.windows(2) {
+        let param = &line[window[0] + 1..window[1]];
+
+        if let Some((key, value)) = param.split_once('=') {
+            params.push((key.to_uppercase(), value.trim_matches('"').to_string()));
+        }
+    }
+
+    Some(Property {
+        name,
+        params,
+        value: line[value_start + 1..].to_string(),
+    })
+}
+
+/**
+ * UID of the first component which isn't a `VTIMEZONE`.
+ */
+pub(crate) fn uid_of(text: &str) -> Option<String> {
+    parse(text)
+        .iter()
+        .flat_map(|x| x.components.iter())
+        .find(|x| x.name != "VTIMEZONE")
+        .and_then(|x| x.value("UID"))
+        .map(str::to_string)
+}
+
+pub(crate) fn uid() -> String {
+    static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+    let now = chrono::Utc::now();
+    let counter = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+    format!(
+        "{}-{counter}@kaldav",
+        now.timestamp_nanos_opt().unwrap_or_else(|| now.timestamp())
+    )
+}
+
+/**
+ * Turns an email address into a `CAL-ADDRESS`, URIs are kept as is.
+ */
+pub(crate) fn cal_address(address: &str) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("mailto:{address}")
+    }
+}
+
+pub(crate) fn format_date_time(date_time: &chrono::DateTime<chrono::Utc>) -> String {
+    date_time.format(DATE_TIME_FORMAT).to_string()
+}
+
+/**
+ * Parses a `DATE` or `DATE-TIME` value. Dates are returned as midnight UTC,
+ * local times are assumed to be in UTC: values with a `TZID` go through
+ * [`crate::Timezones`].
+ */
+pub(crate) fn parse_date_time(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let value = value.trim();
+
+    let naive = if value.len() == 8 {
+        chrono::NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
+    } else {
+        chrono::NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?
+    };
+
+    Some(chrono::Utc.from_utc_datetime(&naive))
+}
+
+pub(crate) fn parse_duration(value: &str) -> Option<chrono::Duration> {
+    let value = value.trim();
+    let (sign, value) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut duration = chrono::Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            _ => {
+                let n = number.parse::<i64>().ok()?;
+                number.clear();
+
+                duration += match (c, in_time) {
+                    ('W', false) => chrono::Duration::weeks(n),
+                    ('D', false) => chrono::Duration::days(n),
+                    ('H', true) => chrono::Duration::hours(n),
+                    ('M', true) => chrono::Duration::minutes(n),
+                    ('S', true) => chrono::Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(duration * sign)
+}
+
+/**
+ * Parses a `PERIOD` value, either explicit (`start/end`) or with a duration
+ * (`start/duration`).
+ */
+pub(crate) fn parse_period(
+    value: &str,
+) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+    let (start, end) = value.split_once('/')?;
+    let start = parse_date_time(start)?;
+
+    let end = if end.contains('P') {
+        start + parse_duration(end)?
+    } else {
+        parse_date_time(end)?
+    };
+
+    Some((start, end))
+}
+
+/**
+ * Start and end of an event or a task, the end defaults to the start (or to
+ * the next day for all-day components, RFC 5545 §3.6.1). Only for UTC data,
+ * like server-expanded recurrences, see [`crate::Timezones`] otherwise.
+ */
+pub(crate) fn bounds(
+    component: &Component,
+) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+    bounds_with(component, |x| parse_date_time(&x.value))
+}
+
+/**
+ * Like [`bounds`], with the date-times converted by `date_time`.
+ */
+pub(crate) fn bounds_with<F>(
+    component: &Component,
+    date_time: F,
+) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)>
+where
+    F: Fn(&Property) -> Option<chrono::DateTime<chrono::Utc>>,
+{
+    let dtstart = component.property("DTSTART")?;
+    let start = date_time(dtstart)?;
+
+    let end = if let Some(end) = component.property("DTEND").or(component.property("DUE")) {
+        date_time(end)?
+    } else if let Some(duration) = component.value("DURATION") {
+        start + parse_duration(duration)?
+    } else if dtstart.is_date() {
+        start + chrono::Duration::days(1)
+    } else {
+        start
+    };
+
+    Some((start, end))
+}
@@ -1,24 +1,50 @@
+mod acl;
+mod agenda;
+mod alarm;
+mod cache;
 mod calendar;
+mod capabilities;
 mod client;
+mod freebusy;
 mod home;
+mod ics;
 mod object;
+mod poller;
 mod principal;
 mod result;
-
+mod schedule;
+mod sharing;
+mod snapshot;
+mod state;
+mod sync;
+mod timezone;
+
+pub use acl::*;
+pub use agenda::*;
+pub use alarm::*;
+pub use cache::*;
 pub use calendar::*;
+pub use capabilities::*;
 pub use client::*;
+pub use freebusy::*;
 pub use home::*;
 pub use object::*;
+pub use poller::*;
 pub use principal::*;
 pub use result::*;
+pub use schedule::*;
+pub use sharing::*;
+pub use snapshot::*;
+pub use state::*;
+pub use sync::*;
+pub use timezone::*;
 
 pub use ikal as ical;
 
 use base64::Engine;
 use embedded_svc::http::client::Client as HttpClient;
-use embedded_svc::utils::io;
 use esp_idf_svc::http::client::{Configuration as HttpConfiguration, EspHttpConnection, Method};
-use esp_idf_svc::io::Write;
+use esp_idf_svc::io::{Read, Write};
 use kaldav_derive::*;
 use std::collections::BTreeMap;
 use std::iter::Iterator;
@@ -47,6 +73,27 @@ pub trait Requestable {
         self.request("PROPFIND", href, Some(body), None)
     }
 
+    /**
+     * `PROPFIND` on the resource itself, without its members.
+     */
+    fn propfind_self<S>(&self, href: S, body: &str) -> Result<String>
+    where
+        S: Into<String>,
+    {
+        let mut headers = BTreeMap::new();
+
+        headers.insert("Depth", "0");
+
+        self.request("PROPFIND", href, Some(body), Some(headers))
+    }
+
+    fn options<S>(&self, href: S) -> Result<Response>
+    where
+        S: Into<String>,
+    {
+        self.send("OPTIONS", href, None, None)
+    }
+
     fn report<S>(&self, href: S, body: &str) -> Result<String>
     where
         S: Into<String>,
@@ -58,13 +105,83 @@ pub trait Requestable {
         self.request("REPORT", href, Some(body), Some(headers))
     }
 
+    fn post<S>(&self, href: S, body: &str, content_type: &str) -> Result<String>
+    where
+        S: Into<String>,
+    {
+        let mut headers = BTreeMap::new();
+
+        headers.insert("Content-Type", content_type);
+
+        self.request("POST", href, Some(body), Some(headers))
+    }
+
+    /**
+     * Stores `body` at `href`, only if the resource still matches `etag` or,
+     * without `etag`, if it doesn't exist yet.
+     */
+    fn put<S>(&self, href: S, body: &str, etag: Option<&str>) -> Result<Response>
+    where
+        S: Into<String>,
+    {
+        let mut headers = BTreeMap::new();
+
+        headers.insert("Content-Type", "text/calendar; charset=utf-8");
+
+        match etag {
+            Some(etag) => headers.insert("If-Match", etag),
+            None => headers.insert("If-None-Match", "*"),
+        };
+
+        self.send("PUT", href, Some(body), Some(headers))
+    }
+
+    fn proppatch<S>(&self, href: S, body: &str) -> Result<String>
+    where
+        S: Into<String>,
+    {
+        let mut headers = BTreeMap::new();
+
+        headers.insert("Content-Type", "application/xml; charset=utf-8");
+
+        self.request("PROPPATCH", href, Some(body), Some(headers))
+    }
+
+    fn delete<S>(&self, href: S, etag: Option<&str>) -> Result
+    where
+        S: Into<String>,
+    {
+        let mut headers = BTreeMap::new();
+
+        if let Some(etag) = etag {
+            headers.insert("If-Match", etag);
+        }
+
+        self.request("DELETE", href, None, Some(headers))?;
+
+        Ok(())
+    }
+
     fn request<S>(
         &self,
         method: &str,
         href: S,
         body: Option<&str>,
-        headers: Option<BTreeMap<&'static str, &'static str>>,
+        headers: Option<BTreeMap<&str, &str>>,
     ) -> Result<String>
+    where
+        S: Into<String>,
+    {
+        self.send(method, href, body, headers).map(|x| x.body)
+    }
+
+    fn send<S>(
+        &self,
+        method: &str,
+        href: S,
+        body: Option<&str>,
+        headers: Option<BTreeMap<&str, &str>>,
+    ) -> Result<Response>
     where
         S: Into<String>,
     {
@@ -79,8 +196,7 @@ pub trait Requestable {
 
         let mut converted_headers: Vec<(&str, &str)> = vec![];
 
-        if let Some(headers) = headers {
-            //todo check for no headers
+        if let Some(headers) = &headers {
             converted_headers = headers.iter().map(|(k, v)| (*k, *v)).collect();
         }
 
@@ -98,7 +214,7 @@ pub trait Requestable {
             );
             converted_headers.push(("Authorization", auth_header.as_str()));
         }
-        let body = body.unwrap();
+        let body = body.unwrap_or_default();
         let binding = body.len().to_string();
         converted_headers.push(("Content-Length", binding.as_str()));
 
@@ -108,6 +224,15 @@ pub trait Requestable {
             "PROPFIND" => Method::Propfind,
             "REPORT" => Method::Report,
             "GET" => Method::Get,
+            "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "DELETE" => Method::Delete,
+            "MKCALENDAR" => Method::MkCalendar,
+            "MKCOL" => Method::MkCol,
+            "PROPPATCH" => Method::Proppatch,
+            "OPTIONS" => Method::Options,
+            "COPY" => Method::Copy,
+            "MOVE" => Method::Move,
             _ => panic!("Method not supported"),
         };
 
@@ -117,22 +242,89 @@ pub trait Requestable {
 
         let mut response = request.submit()?;
 
-        let mut buf = [0u8; 8192];
-        let bytes_read = io::try_read_full(&mut response, &mut buf).map_err(|e| e.0)?;
-        let text = std::str::from_utf8(&buf[0..bytes_read]).unwrap();
+        let status = response.status();
+        let mut response_headers = BTreeMap::new();
+
+        for name in RESPONSE_HEADERS {
+            if let Some(value) = response.header(name) {
+                response_headers.insert(name.to_string(), value.to_string());
+            }
+        }
 
-        if embedded_svc::http::status::OK.contains(&response.status()) {
+        let mut content = Vec::new();
+        let mut buf = [0u8; 1024];
+
+        loop {
+            let len = response.read(&mut buf)?;
+
+            if len == 0 {
+                break;
+            }
+
+            content.extend_from_slice(&buf[..len]);
+        }
+
+        let text = String::from_utf8_lossy(&content).into_owned();
+
+        if embedded_svc::http::status::OK.contains(&status) {
             println!("Response: {}", text);
-            Ok(text.to_string())
+            Ok(Response {
+                status,
+                headers: response_headers,
+                body: text,
+            })
         } else {
-            Err(Error::new(format!(
-                "{method} {href}: {}",
-                response.status()
-            )))
+            Err(Error::Status {
+                method: method.to_string(),
+                href,
+                status,
+            })
         }
     }
 }
 
+const RESPONSE_HEADERS: [&str; 6] = [
+    "Allow",
+    "Content-Type",
+    "DAV",
+    "ETag",
+    "Location",
+    "Schedule-Tag",
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct Response {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/**
+ * Path of an URL, as expected in the `href` of a request body.
+ */
+pub(crate) fn path(url: &str) -> &str {
+    match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|x| &rest[x..]).unwrap_or("/"),
+        None => url,
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 pub trait Xmlable {
     fn url(&self) -> &str;
 
@@ -144,6 +336,7 @@ pub trait Xmlable {
         let mut context = sxd_xpath::Context::new();
         context.set_namespace("d", "DAV:");
         context.set_namespace("cal", "urn:ietf:params:xml:ns:caldav");
+        context.set_namespace("cs", "http://calendarserver.org/ns/");
         context.set_namespace("x1", "http://apple.com/ns/ical/");
 
         let factory = sxd_xpath::Factory::new();
@@ -159,7 +352,7 @@ pub trait Xmlable {
 
         if let sxd_xpath::Value::Nodeset(nodes) = nodes {
             for node in nodes.iter() {
-                results.push(String::from(node.text().unwrap().text()));
+                results.push(node.string_value());
             }
         }
 
@@ -1,8 +1,11 @@
+use crate::Children;
 use crate::Requestable;
+use crate::Xmlable;
 
 pub struct Iterator {
     objects: Vec<Object>,
     current: usize,
+    snapshot: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl Iterator {
@@ -10,9 +13,27 @@ impl Iterator {
         Self {
             objects,
             current: 0,
+            snapshot: None,
         }
     }
 
+    pub(crate) fn from_snapshot(
+        objects: Vec<Object>,
+        taken: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            snapshot: Some(taken),
+            ..Self::from(objects)
+        }
+    }
+
+    /**
+     * When the objects were saved, if they come from an offline snapshot.
+     */
+    pub fn snapshot(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.snapshot
+    }
+
     pub fn is_empty(&self) -> bool {
         self.objects.is_empty()
     }
@@ -22,8 +43,7 @@ impl Iterator {
     }
 
     fn get(&self, index: usize) -> crate::Result<ikal::VCalendar> {
-        let object = &self.objects[index];
-        let contents = object.get(object.url.clone())?;
+        let contents = self.objects[index].data()?;
         ikal::VCalendar::try_from(contents).map_err(crate::Error::from)
     }
 }
@@ -45,6 +65,161 @@ impl std::iter::Iterator for Iterator {
 pub struct Object {
     url: String,
     auth: Option<crate::Authorization>,
+    pub(crate) etag: Option<String>,
+    pub(crate) data: Option<String>,
+}
+
+impl Object {
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /**
+     * The iCalendar data, downloaded unless the server already sent it.
+     */
+    pub fn data(&self) -> crate::Result<String> {
+        match &self.data {
+            Some(data) => Ok(data.clone()),
+            None => self.get(self.url.clone()),
+        }
+    }
+
+    pub fn attendees(&self) -> crate::Result<Vec<crate::Attendee>> {
+        Ok(crate::Attendee::from_data(&self.data()?))
+    }
+
+    pub fn copy_to(&self, calendar: &crate::Calendar) -> crate::Result<Self> {
+        self.transfer("COPY", calendar)
+    }
+
+    pub fn move_to(&self, calendar: &crate::Calendar) -> crate::Result<Self> {
+        self.transfer("MOVE", calendar)
+    }
+
+    /**
+     * Copies or moves the object in `calendar` with the same name, without
+     * overwriting an existing one. When the server refuses the operation
+     * between collections, the object is downloaded and stored again.
+     */
+    fn transfer(&self, method: &str, calendar: &crate::Calendar) -> crate::Result<Self> {
+        let name = self
+            .url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        let destination = format!("{}/{name}", calendar.url().trim_end_matches('/'));
+
+        let mut headers = std::collections::BTreeMap::new();
+        headers.insert("Destination", destination.as_str());
+        headers.insert("Overwrite", "F");
+
+        match self.send(method, self.url.clone(), None, Some(headers)) {
+            Ok(_) => (),
+            Err(err) if err.is_unsupported() || err.status() == Some(502) => {
+                calendar.put(destination.as_str(), &self.data()?, None)?;
+
+                if method == "MOVE" {
+                    self.delete(self.url.clone(), self.etag())?;
+                }
+            }
+            Err(err) => return Err(err),
+        }
+
+        let mut object = Self::new(destination, &Default::default());
+        object.set_auth(calendar.auth());
+        object.fetch()?;
+
+        Ok(object)
+    }
+
+    /**
+     * Dismisses `alarm`, its `VALARM` is marked as acknowledged.
+     */
+    pub fn acknowledge(&mut self, alarm: &crate::Alarm) -> crate::Result {
+        self.update_alarm(|data| crate::alarm::acknowledge(data, alarm, chrono::Utc::now()))
+    }
+
+    /**
+     * Dismisses `alarm` and reminds it again at `until` with a snooze alarm.
+     */
+    pub fn snooze<Tz>(&mut self, alarm: &crate::Alarm, until: chrono::DateTime<Tz>) -> crate::Result
+    where
+        Tz: chrono::TimeZone,
+    {
+        let until = until.with_timezone(&chrono::Utc);
+
+        self.update_alarm(|data| crate::alarm::snooze(data, alarm, until, chrono::Utc::now()))
+    }
+
+    /**
+     * Rewrites the data with `f`, then saves it if nobody modified the object
+     * meanwhile.
+     */
+    fn update_alarm<F>(&mut self, f: F) -> crate::Result
+    where
+        F: FnOnce(&str) -> Option<String>,
+    {
+        if self.data.is_none() || self.etag.is_none() {
+            self.fetch()?;
+        }
+
+        let Some(data) = f(self.data.as_deref().unwrap_or_default()) else {
+            return Err(crate::Error::Misc(format!(
+                "Alarm not found in {}",
+                self.url
+            )));
+        };
+
+        let response = self.put(self.url.clone(), &data, self.etag())?;
+
+        match response.header("ETag") {
+            Some(etag) => {
+                self.etag = Some(etag.to_string());
+                self.data = Some(data);
+            }
+            // The server modified the data
+            None => self.fetch()?,
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Downloads the object again, with its current ETag.
+     */
+    pub(crate) fn fetch(&mut self) -> crate::Result {
+        let response = self.send("GET", self.url.clone(), None, None)?;
+
+        self.etag = response.header("ETag").map(str::to_string);
+        self.data = Some(response.body);
+
+        Ok(())
+    }
+
+    /**
+     * Reads objects from a multistatus with `getetag` and `calendar-data`
+     * properties.
+     */
+    pub(crate) fn from_response<P>(parent: &P, response: &str) -> Vec<Self>
+    where
+        P: crate::Children,
+    {
+        let mut objects: Vec<Self> = parent.to_vec(response, "//d:response/d:href/text()");
+
+        for (i, object) in objects.iter_mut().enumerate() {
+            let xpath = format!("//d:response[{}]/d:propstat/d:prop", i + 1);
+
+            object.etag = P::xml(response, &format!("{xpath}/d:getetag/text()"))
+                .into_iter()
+                .next();
+            object.data = P::xml(response, &format!("{xpath}/cal:calendar-data/text()"))
+                .into_iter()
+                .next();
+        }
+
+        objects
+    }
 }
 
 impl crate::Children for Object {
@@ -55,6 +230,8 @@ impl crate::Children for Object {
         Self {
             url: url.into(),
             auth: None,
+            etag: None,
+            data: None,
         }
     }
 }
@@ -73,4 +250,4 @@ impl crate::Requestable for Object {
     fn set_auth(&mut self, auth: Option<crate::Authorization>) {
         self.auth = auth;
     }
-}
\ No newline at end of file
+}
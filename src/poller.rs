@@ -0,0 +1,71 @@
+use crate::Xmlable;
+
+/**
+ * Watches calendars for changes with a single `PROPFIND` on their home.
+ */
+#[derive(Clone, Debug)]
+pub struct Poller {
+    home: crate::Home,
+    calendars: Vec<crate::Calendar>,
+    /**
+     * URLs of the calendars without ctag nor sync token already reported.
+     */
+    untagged: Vec<String>,
+}
+
+impl Poller {
+    /**
+     * `calendars` are compared to their state when they were listed, a
+     * calendar without ctag nor sync token is only reported as changed on
+     * the first poll.
+     */
+    pub fn new(home: crate::Home, calendars: Vec<crate::Calendar>) -> Self {
+        Self {
+            home,
+            calendars,
+            untagged: Vec::new(),
+        }
+    }
+
+    pub fn calendars(&self) -> &[crate::Calendar] {
+        &self.calendars
+    }
+
+    /**
+     * Calls `callback` with the calendars changed since the last poll, if
+     * any. Calendars removed from the server are ignored.
+     */
+    pub fn poll<F>(&mut self, mut callback: F) -> crate::Result<bool>
+    where
+        F: FnMut(&[crate::Calendar]),
+    {
+        let remote = self.home.calendars()?;
+        let mut changed = Vec::new();
+
+        for calendar in &mut self.calendars {
+            let Some(current) = remote.values().find(|x| x.url() == calendar.url()) else {
+                continue;
+            };
+
+            let unseen =
+                current.tag().is_none() && !self.untagged.iter().any(|x| x == calendar.url());
+
+            if unseen || current.tag() != calendar.tag() {
+                if unseen {
+                    self.untagged.push(calendar.url().to_string());
+                }
+
+                *calendar = current.clone();
+                changed.push(current.clone());
+            }
+        }
+
+        if changed.is_empty() {
+            return Ok(false);
+        }
+
+        callback(&changed);
+
+        Ok(true)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::Children;
 use crate::Requestable;
+use crate::Xmlable;
 use std::convert::Into;
 
 #[derive(Clone, Debug, Default, crate::Object)]
@@ -8,24 +9,322 @@ pub struct Principal {
     auth: Option<crate::Authorization>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserType {
+    Individual,
+    Group,
+    Resource,
+    Room,
+    Unknown,
+}
+
+impl UserType {
+    fn from_property(value: Option<&str>) -> Self {
+        match value.map(str::to_uppercase).as_deref() {
+            None | Some("INDIVIDUAL") => Self::Individual,
+            Some("GROUP") => Self::Group,
+            Some("RESOURCE") => Self::Resource,
+            Some("ROOM") => Self::Room,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    ReadWrite,
+}
+
+/**
+ * A principal whose calendars are managed through a CalendarServer proxy
+ * group (`calendar-proxy-read` or `calendar-proxy-write`).
+ */
+#[derive(Clone, Debug)]
+pub struct Delegation {
+    pub principal: Principal,
+    pub access: Access,
+}
+
+impl Delegation {
+    pub fn is_read_only(&self) -> bool {
+        self.access == Access::Read
+    }
+
+    pub fn homes(&self) -> crate::Result<Vec<crate::Home>> {
+        self.principal.homes()
+    }
+
+    /**
+     * Calendars of every home, named as by [`crate::Client::calendars`].
+     */
+    pub fn calendars(&self) -> crate::Result<std::collections::BTreeMap<String, crate::Calendar>> {
+        crate::client::calendars(&self.homes()?)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Properties {
+    pub display_name: Option<String>,
+    /**
+     * The `calendar-user-address-set`, `mailto:` URIs first.
+     */
+    pub addresses: Vec<String>,
+    pub user_type: UserType,
+    pub homes: Vec<crate::Home>,
+    pub schedule_inbox: Option<String>,
+    pub schedule_outbox: Option<String>,
+    pub proxy_read_for: Vec<Principal>,
+    pub proxy_write_for: Vec<Principal>,
+    pub notifications: Option<String>,
+}
+
 impl Principal {
-    pub fn home(&self) -> crate::Result<crate::Home> {
+    pub fn properties(&self) -> crate::Result<Properties> {
         let response = self.propfind(
             &self.url,
             r#"
-<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:cs="http://calendarserver.org/ns/">
   <d:prop>
      <d:displayname />
      <c:calendar-home-set />
+     <c:calendar-user-address-set />
+     <c:calendar-user-type />
+     <c:schedule-inbox-URL />
+     <c:schedule-outbox-URL />
+     <cs:calendar-proxy-read-for />
+     <cs:calendar-proxy-write-for />
+     <cs:notification-URL />
+  </d:prop>
+</d:propfind>
+"#,
+        )?;
+
+        let prop = "//d:response[1]/d:propstat/d:prop";
+        let first = |xpath: &str| {
+            Self::xml(&response, &format!("{prop}/{xpath}"))
+                .into_iter()
+                .next()
+        };
+
+        let mut addresses = Self::xml(
+            &response,
+            &format!("{prop}/cal:calendar-user-address-set/d:href/text()"),
+        );
+        addresses.sort_by_key(|x| !x.to_lowercase().starts_with("mailto:"));
+
+        Ok(Properties {
+            display_name: first("d:displayname/text()"),
+            addresses,
+            user_type: UserType::from_property(first("cal:calendar-user-type/text()").as_deref()),
+            homes: self.to_vec(
+                &response,
+                &format!("{prop}/cal:calendar-home-set/d:href/text()"),
+            ),
+            schedule_inbox: first("cal:schedule-inbox-URL/d:href/text()")
+                .map(|x| self.append_host(x)),
+            schedule_outbox: first("cal:schedule-outbox-URL/d:href/text()")
+                .map(|x| self.append_host(x)),
+            proxy_read_for: self.to_vec(
+                &response,
+                &format!("{prop}/cs:calendar-proxy-read-for/d:href/text()"),
+            ),
+            proxy_write_for: self.to_vec(
+                &response,
+                &format!("{prop}/cs:calendar-proxy-write-for/d:href/text()"),
+            ),
+            notifications: first("cs:notification-URL/d:href/text()").map(|x| self.append_host(x)),
+        })
+    }
+
+    pub fn home(&self) -> crate::Result<crate::Home> {
+        self.homes()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| crate::Error::Misc(format!("No home found for {}", self.url)))
+    }
+
+    pub fn homes(&self) -> crate::Result<Vec<crate::Home>> {
+        Ok(self.properties()?.homes)
+    }
+
+    pub fn display_name(&self) -> crate::Result<Option<String>> {
+        Ok(self.properties()?.display_name)
+    }
+
+    pub fn addresses(&self) -> crate::Result<Vec<String>> {
+        Ok(self.properties()?.addresses)
+    }
+
+    /**
+     * Principals this one is a proxy for, a principal in both proxy groups
+     * is only listed with the read-write access.
+     */
+    pub fn delegations(&self) -> crate::Result<Vec<Delegation>> {
+        let properties = self.properties()?;
+
+        let write = properties
+            .proxy_write_for
+            .into_iter()
+            .map(|principal| Delegation {
+                principal,
+                access: Access::ReadWrite,
+            });
+        let read = properties
+            .proxy_read_for
+            .into_iter()
+            .map(|principal| Delegation {
+                principal,
+                access: Access::Read,
+            });
+
+        let mut delegations: Vec<Delegation> = Vec::new();
+
+        for delegation in write.chain(read) {
+            if !delegations
+                .iter()
+                .any(|x| x.principal.url() == delegation.principal.url())
+            {
+                delegations.push(delegation);
+            }
+        }
+
+        Ok(delegations)
+    }
+
+    /**
+     * Calendars shared with this principal, waiting in the notification
+     * collection.
+     */
+    pub fn share_invitations(&self) -> crate::Result<Vec<crate::ShareInvitation>> {
+        let Some(notifications) = self.properties()?.notifications else {
+            return Ok(Vec::new());
+        };
+
+        let response = self.propfind(
+            notifications.as_str(),
+            r#"
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+     <d:resourcetype />
   </d:prop>
 </d:propfind>
 "#,
         )?;
 
-        self.one(
+        let mut invitations = Vec::new();
+
+        for object in self.to_vec::<crate::Object>(&response, "//d:response/d:href/text()") {
+            if object.url().trim_end_matches('/') == notifications.trim_end_matches('/') {
+                continue;
+            }
+
+            let notification = object.get(object.url())?;
+
+            if let Some(invitation) =
+                crate::ShareInvitation::from_notification(object, &notification)
+            {
+                invitations.push(invitation);
+            }
+        }
+
+        Ok(invitations)
+    }
+
+    pub fn schedule_outbox(&self) -> crate::Result<Option<String>> {
+        Ok(self.properties()?.schedule_outbox)
+    }
+
+    pub fn schedule_inbox(&self) -> crate::Result<crate::Inbox> {
+        let properties = self.properties()?;
+
+        let url = properties
+            .schedule_inbox
+            .ok_or_else(|| crate::Error::Misc("No schedule inbox found".to_string()))?;
+
+        let mut inbox = crate::Inbox::new(url, &Default::default());
+        inbox.set_auth(self.auth());
+        inbox.addresses = properties.addresses;
+
+        Ok(inbox)
+    }
+
+    /**
+     * Asks the server for the busy time of `attendees` (RFC 6638 §5), the
+     * result contains one response per recipient.
+     */
+    pub fn lookup_free_busy<S, Tz>(
+        &self,
+        attendees: &[S],
+        start: chrono::DateTime<Tz>,
+        end: chrono::DateTime<Tz>,
+    ) -> crate::Result<Vec<crate::ScheduleResponse>>
+    where
+        S: AsRef<str>,
+        Tz: chrono::TimeZone,
+    {
+        let properties = self.properties()?;
+
+        let outbox = properties
+            .schedule_outbox
+            .ok_or_else(|| crate::Error::Misc("No schedule outbox found".to_string()))?;
+        let organizer = properties
+            .addresses
+            .into_iter()
+            .next()
+            .ok_or_else(|| crate::Error::Misc("No calendar user address found".to_string()))?;
+
+        let attendees = attendees
+            .iter()
+            .map(|x| format!("ATTENDEE:{}\r\n", crate::ics::cal_address(x.as_ref())))
+            .collect::<String>();
+
+        let body = format!(
+            "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//kaldav//EN\r
+METHOD:REQUEST\r
+BEGIN:VFREEBUSY\r
+UID:{}\r
+DTSTAMP:{}\r
+DTSTART:{}\r
+DTEND:{}\r
+ORGANIZER:{organizer}\r
+{attendees}END:VFREEBUSY\r
+END:VCALENDAR\r
+",
+            crate::ics::uid(),
+            crate::ics::format_date_time(&chrono::Utc::now()),
+            crate::ics::format_date_time(&start.with_timezone(&chrono::Utc)),
+            crate::ics::format_date_time(&end.with_timezone(&chrono::Utc)),
+        );
+
+        let response = self.post(outbox, &body, "text/calendar; charset=utf-8")?;
+        let recipients = Self::xml(
             &response,
-            "//d:response[1]//cal:calendar-home-set/d:href/text()",
-        )
-        .ok_or_else(|| crate::Error::Misc("No home found".to_string()))
+            "//cal:schedule-response/cal:response/cal:recipient/d:href/text()",
+        );
+
+        let responses = recipients
+            .into_iter()
+            .map(|recipient| {
+                let xpath = format!(
+                    "//cal:schedule-response/cal:response[cal:recipient/d:href/text() = '{recipient}']"
+                );
+                let status = Self::xml(&response, &format!("{xpath}/cal:request-status/text()"));
+                let data = Self::xml(&response, &format!("{xpath}/cal:calendar-data/text()"));
+
+                crate::ScheduleResponse {
+                    recipient,
+                    status: status.into_iter().next().unwrap_or_default(),
+                    periods: data
+                        .iter()
+                        .flat_map(|x| crate::freebusy::parse(x))
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(responses)
     }
 }
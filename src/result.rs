@@ -13,6 +13,14 @@ pub enum Error {
     Parser(#[from] ikal::Error),
     #[error("HTTP error: {0}")]
     Http(#[from] EspIOError),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("{method} {href}: {status}")]
+    Status {
+        method: String,
+        href: String,
+        status: u16,
+    },
 }
 
 impl Error {
@@ -22,4 +30,32 @@ impl Error {
     {
         Self::Misc(message.into())
     }
+
+    pub(crate) fn storage<E>(err: E) -> Self
+    where
+        E: std::fmt::Display,
+    {
+        Self::Storage(err.to_string())
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /**
+     * The server refused the method or the report, rather than failing it.
+     */
+    pub(crate) fn is_unsupported(&self) -> bool {
+        matches!(self.status(), Some(400 | 403 | 405 | 415 | 501))
+    }
+
+    /**
+     * The server couldn't be reached.
+     */
+    pub(crate) fn is_offline(&self) -> bool {
+        matches!(self, Self::Http(_) | Self::Io(_))
+    }
 }
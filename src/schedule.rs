@@ -0,0 +1,266 @@
+use crate::Requestable;
+use crate::Xmlable;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartStat {
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+    Delegated,
+}
+
+impl PartStat {
+    fn from_param(param: Option<&str>) -> Self {
+        match param.map(str::to_uppercase).as_deref() {
+            Some("ACCEPTED") => Self::Accepted,
+            Some("DECLINED") => Self::Declined,
+            Some("TENTATIVE") => Self::Tentative,
+            Some("DELEGATED") => Self::Delegated,
+            _ => Self::NeedsAction,
+        }
+    }
+}
+
+impl std::fmt::Display for PartStat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            Self::NeedsAction => "NEEDS-ACTION",
+            Self::Accepted => "ACCEPTED",
+            Self::Declined => "DECLINED",
+            Self::Tentative => "TENTATIVE",
+            Self::Delegated => "DELEGATED",
+        };
+
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone, Debug, Default, crate::Object)]
+pub struct Inbox {
+    url: String,
+    auth: Option<crate::Authorization>,
+    pub(crate) addresses: Vec<String>,
+}
+
+impl Inbox {
+    /**
+     * iTIP messages waiting in the scheduling inbox (RFC 6638 §2.2).
+     */
+    pub fn messages(&self) -> crate::Result<Vec<Message>> {
+        let response = self.report(
+            &self.url,
+            r#"
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <d:getetag />
+        <c:calendar-data />
+    </d:prop>
+    <c:filter>
+        <c:comp-filter name="VCALENDAR" />
+    </c:filter>
+</c:calendar-query>
+"#,
+        )?;
+
+        let messages = crate::Object::from_response(self, &response)
+            .into_iter()
+            .map(|object| Message::new(object, &self.addresses))
+            .collect();
+
+        Ok(messages)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub method: Option<String>,
+    pub component: Option<String>,
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub organizer: Option<String>,
+    object: crate::Object,
+    addresses: Vec<String>,
+}
+
+impl Message {
+    fn new(object: crate::Object, addresses: &[String]) -> Self {
+        let calendar = object
+            .data
+            .as_deref()
+            .map(crate::ics::parse)
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let component = calendar.components.iter().find(|x| x.name != "VTIMEZONE");
+        let value = |name: &str| component.and_then(|x| x.value(name)).map(str::to_string);
+
+        Self {
+            method: calendar.value("METHOD").map(str::to_string),
+            component: component.map(|x| x.name.clone()),
+            uid: value("UID"),
+            summary: value("SUMMARY"),
+            organizer: value("ORGANIZER"),
+            addresses: addresses.to_vec(),
+            object,
+        }
+    }
+
+    pub fn object(&self) -> &crate::Object {
+        &self.object
+    }
+
+    pub fn accept(&self, calendar: &crate::Calendar) -> crate::Result {
+        self.respond(calendar, PartStat::Accepted)
+    }
+
+    pub fn decline(&self, calendar: &crate::Calendar) -> crate::Result {
+        self.respond(calendar, PartStat::Declined)
+    }
+
+    pub fn tentative(&self, calendar: &crate::Calendar) -> crate::Result {
+        self.respond(calendar, PartStat::Tentative)
+    }
+
+    /**
+     * Updates the attendee copy in `calendar`, created from the message if the
+     * server didn't deliver it, then removes the message from the inbox.
+     *
+     * Only invitations (`METHOD:REQUEST`) can be answered. Delegating needs
+     * a `DELEGATED-TO` parameter, it isn't supported.
+     */
+    pub fn respond(&self, calendar: &crate::Calendar, partstat: PartStat) -> crate::Result {
+        if partstat == PartStat::Delegated {
+            return Err(crate::Error::new(
+                "Delegating an invitation isn't supported",
+            ));
+        }
+
+        if let Some(method) = &self.method {
+            if !method.eq_ignore_ascii_case("REQUEST") {
+                return Err(crate::Error::Misc(format!(
+                    "Can't answer a {method} scheduling message {}",
+                    self.object.url()
+                )));
+            }
+        }
+
+        let (Some(component), Some(uid)) = (&self.component, &self.uid) else {
+            return Err(crate::Error::Misc(format!(
+                "Invalid scheduling message {}",
+                self.object.url()
+            )));
+        };
+
+        let partstat = partstat.to_string();
+
+        match calendar.object_by_uid(component, uid)? {
+            Some(copy) => {
+                let data = crate::ics::set_partstat(&copy.data()?, &self.addresses, &partstat);
+                calendar.put(copy.url(), &data, copy.etag())?;
+            }
+            None => {
+                let data = crate::ics::without_method(&self.object.data()?);
+                let data = crate::ics::set_partstat(&data, &self.addresses, &partstat);
+                calendar.put(calendar.object_url(uid), &data, None)?;
+            }
+        }
+
+        self.delete()
+    }
+
+    /**
+     * Removes the message from the inbox without answering it.
+     */
+    pub fn delete(&self) -> crate::Result {
+        self.object.delete(self.object.url(), self.object.etag())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attendee {
+    pub address: String,
+    pub name: Option<String>,
+    pub partstat: PartStat,
+    /**
+     * Delivery status set by the server on the organizer copy (RFC 6638
+     * §7.3), `1.x` and `2.x` codes mean the invitation was sent.
+     */
+    pub schedule_status: Option<String>,
+}
+
+impl Attendee {
+    pub(crate) fn from_data(data: &str) -> Vec<Self> {
+        crate::ics::parse(data)
+            .iter()
+            .flat_map(|x| x.components.iter())
+            .find(|x| x.name != "VTIMEZONE")
+            .map(|component| {
+                component
+                    .properties("ATTENDEE")
+                    .map(|property| Self {
+                        address: property.value.clone(),
+                        name: property.param("CN").map(str::to_string),
+                        partstat: PartStat::from_param(property.param("PARTSTAT")),
+                        schedule_status: property.param("SCHEDULE-STATUS").map(str::to_string),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Meeting {
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub end: chrono::DateTime<chrono::Utc>,
+    /**
+     * Email addresses or `CAL-ADDRESS` URIs.
+     */
+    pub attendees: Vec<String>,
+}
+
+impl Meeting {
+    pub(crate) fn to_ics(&self, uid: &str, organizer: &str) -> String {
+        let mut event = crate::ics::Component::new("VEVENT");
+
+        event.push("UID", &[], uid);
+        event.push(
+            "DTSTAMP",
+            &[],
+            &crate::ics::format_date_time(&chrono::Utc::now()),
+        );
+        event.push("DTSTART", &[], &crate::ics::format_date_time(&self.start));
+        event.push("DTEND", &[], &crate::ics::format_date_time(&self.end));
+        event.push("SUMMARY", &[], &crate::ics::escape_text(&self.summary));
+
+        if let Some(description) = &self.description {
+            event.push("DESCRIPTION", &[], &crate::ics::escape_text(description));
+        }
+
+        if let Some(location) = &self.location {
+            event.push("LOCATION", &[], &crate::ics::escape_text(location));
+        }
+
+        event.push("ORGANIZER", &[], organizer);
+
+        for attendee in &self.attendees {
+            event.push(
+                "ATTENDEE",
+                &[
+                    ("CUTYPE", "INDIVIDUAL"),
+                    ("ROLE", "REQ-PARTICIPANT"),
+                    ("PARTSTAT", "NEEDS-ACTION"),
+                    ("RSVP", "TRUE"),
+                ],
+                &crate::ics::cal_address(attendee),
+            );
+        }
+
+        crate::ics::Component::calendar(event).to_string()
+    }
+}
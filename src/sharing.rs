@@ -0,0 +1,146 @@
+use crate::Requestable;
+use crate::Xmlable;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InviteStatus {
+    NoResponse,
+    Accepted,
+    Declined,
+    Invalid,
+}
+
+impl InviteStatus {
+    pub(crate) fn from_response<X>(response: &str, xpath: &str) -> Self
+    where
+        X: crate::Xmlable,
+    {
+        let exists = |name: &str| !X::xml(response, &format!("{xpath}/{name}")).is_empty();
+
+        if exists("cs:invite-accepted") {
+            Self::Accepted
+        } else if exists("cs:invite-declined") {
+            Self::Declined
+        } else if exists("cs:invite-invalid") {
+            Self::Invalid
+        } else {
+            Self::NoResponse
+        }
+    }
+}
+
+impl crate::Access {
+    pub(crate) fn from_response<X>(response: &str, xpath: &str) -> Self
+    where
+        X: crate::Xmlable,
+    {
+        if X::xml(response, &format!("{xpath}/cs:access/cs:read-write")).is_empty() {
+            Self::Read
+        } else {
+            Self::ReadWrite
+        }
+    }
+
+    pub(crate) fn element(&self) -> &'static str {
+        match self {
+            Self::Read => "<cs:read />",
+            Self::ReadWrite => "<cs:read-write />",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sharee {
+    pub address: String,
+    pub name: Option<String>,
+    pub access: crate::Access,
+    pub status: InviteStatus,
+}
+
+/**
+ * An invitation to a calendar shared by another user, found in the
+ * notification collection.
+ */
+#[derive(Clone, Debug)]
+pub struct ShareInvitation {
+    pub uid: String,
+    pub host_url: String,
+    pub organizer: Option<String>,
+    pub summary: Option<String>,
+    pub access: crate::Access,
+    pub status: InviteStatus,
+    address: Option<String>,
+    object: crate::Object,
+}
+
+impl ShareInvitation {
+    pub(crate) fn from_notification(object: crate::Object, response: &str) -> Option<Self> {
+        let xpath = "//cs:notification/cs:invite-notification";
+        let first = |name: &str| {
+            crate::Object::xml(response, &format!("{xpath}/{name}"))
+                .into_iter()
+                .next()
+        };
+
+        Some(Self {
+            uid: first("cs:uid/text()")?,
+            host_url: first("cs:hosturl/d:href/text()")?,
+            organizer: first("cs:organizer/d:href/text()"),
+            summary: first("cs:summary/text()"),
+            access: crate::Access::from_response::<crate::Object>(response, xpath),
+            status: InviteStatus::from_response::<crate::Object>(response, xpath),
+            address: first("d:href/text()"),
+            object,
+        })
+    }
+
+    pub fn accept(&self, home: &crate::Home) -> crate::Result {
+        self.reply(home, true)
+    }
+
+    pub fn decline(&self, home: &crate::Home) -> crate::Result {
+        self.reply(home, false)
+    }
+
+    /**
+     * Answers the invitation on the calendar home of the sharee, then removes
+     * the notification.
+     */
+    fn reply(&self, home: &crate::Home, accept: bool) -> crate::Result {
+        let status = if accept {
+            "<cs:invite-accepted />"
+        } else {
+            "<cs:invite-declined />"
+        };
+        let address = self
+            .address
+            .as_deref()
+            .map(|x| format!("<d:href>{}</d:href>", crate::escape(x)))
+            .unwrap_or_default();
+        let summary = self
+            .summary
+            .as_deref()
+            .map(|x| format!("<cs:summary>{}</cs:summary>", crate::escape(x)))
+            .unwrap_or_default();
+
+        let body = format!(
+            r#"
+<cs:invite-reply xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+  {address}
+  {status}
+  <cs:hosturl>
+    <d:href>{}</d:href>
+  </cs:hosturl>
+  <cs:in-reply-to>{}</cs:in-reply-to>
+  {summary}
+</cs:invite-reply>
+"#,
+            crate::escape(&self.host_url),
+            crate::escape(&self.uid),
+        );
+
+        self.object
+            .post(home.url(), &body, "application/xml; charset=utf-8")?;
+
+        self.object.delete(self.object.url(), None)
+    }
+}
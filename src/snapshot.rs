@@ -0,0 +1,178 @@
+use crate::Children;
+use crate::Requestable;
+use std::collections::BTreeMap;
+
+const KEY: &str = "snapshot";
+
+/**
+ * Calendars and objects as they were at `taken`, used to answer when the
+ * network is down.
+ *
+ * The objects are stored in the same format as a [`crate::Cache`], both can
+ * share a storage.
+ */
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub taken: chrono::DateTime<chrono::Utc>,
+    state: crate::State,
+    objects: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    pub(crate) fn take<S>(mut state: crate::State, storage: &mut S) -> crate::Result<Self>
+    where
+        S: crate::Storage,
+    {
+        let mut cache = crate::Cache::new(&mut *storage);
+        let mut objects = BTreeMap::new();
+
+        let calendars: Vec<_> = state.calendars.iter().map(|x| state.calendar(x)).collect();
+
+        for (calendar, current) in calendars.iter().zip(&mut state.calendars) {
+            let mut etags = BTreeMap::new();
+
+            for object in calendar.pull(None, &mut cache)?.objects {
+                let href = crate::Xmlable::url(&object).to_string();
+
+                if let (Some(etag), Some(data)) = (object.etag, object.data) {
+                    etags.insert(href.clone(), etag);
+                    objects.insert(href, data);
+                }
+            }
+
+            current.etags = etags;
+        }
+
+        let snapshot = Self {
+            taken: chrono::Utc::now(),
+            state,
+            objects,
+        };
+
+        snapshot.save(storage)?;
+
+        Ok(snapshot)
+    }
+
+    /**
+     * The last snapshot saved in `storage`, if any.
+     */
+    pub fn load<S>(storage: &mut S) -> crate::Result<Option<Self>>
+    where
+        S: crate::Storage,
+    {
+        let Some(bytes) = storage.get(KEY)? else {
+            return Ok(None);
+        };
+
+        let (Some(taken), Some(state)) = (bytes.get(..8), bytes.get(8..)) else {
+            return Err(crate::Error::new("Invalid snapshot"));
+        };
+
+        let taken = i64::from_be_bytes(taken.try_into().unwrap_or_default());
+        let taken = chrono::DateTime::from_timestamp(taken, 0)
+            .ok_or_else(|| crate::Error::new("Invalid snapshot"))?;
+        let state = crate::State::from_bytes(state)?;
+
+        let cache = crate::Cache::new(&mut *storage);
+        let mut objects = BTreeMap::new();
+
+        for calendar in &state.calendars {
+            for (href, etag) in &calendar.etags {
+                if let Some(data) = cache.object(href, etag)? {
+                    objects.insert(href.clone(), data);
+                }
+            }
+        }
+
+        Ok(Some(Self {
+            taken,
+            state,
+            objects,
+        }))
+    }
+
+    /**
+     * Saves the calendars list, the objects are already in the storage.
+     */
+    fn save<S>(&self, storage: &mut S) -> crate::Result
+    where
+        S: crate::Storage,
+    {
+        let mut bytes = self.taken.timestamp().to_be_bytes().to_vec();
+        bytes.extend(self.state.to_bytes());
+
+        storage.set(KEY, &bytes)
+    }
+
+    pub(crate) fn calendars(
+        &self,
+        auth: Option<crate::Authorization>,
+    ) -> BTreeMap<String, crate::Calendar> {
+        let mut state = self.state.clone();
+        state.set_auth(auth);
+
+        let mut calendars = state.calendars();
+
+        for calendar in calendars.values_mut() {
+            calendar.taken = Some(self.taken);
+        }
+
+        calendars
+    }
+
+    /**
+     * Objects of `calendar` containing a `component` between `start` and
+     * `end`. Recurring components aren't expanded, they match from their
+     * first occurrence.
+     */
+    pub(crate) fn objects(
+        &self,
+        calendar: &crate::Calendar,
+        component: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> crate::object::Iterator {
+        let url = crate::Xmlable::url(calendar);
+        let Some(state) = self.state.calendars.iter().find(|x| x.url == url) else {
+            return crate::object::Iterator::from_snapshot(Vec::new(), self.taken);
+        };
+
+        let matches = |data: &str| {
+            crate::ics::parse(data).iter().any(|vcalendar| {
+                let timezones = crate::Timezones::embedded(vcalendar);
+
+                vcalendar.children(component).any(|x| {
+                    let Some((first, last)) = timezones.component_bounds(x) else {
+                        return start.is_none() && end.is_none();
+                    };
+                    let recurring = x.property("RRULE").is_some() || x.property("RDATE").is_some();
+
+                    end.is_none_or(|end| first < end)
+                        && (recurring || start.is_none_or(|start| last > start))
+                })
+            })
+        };
+
+        let objects = state
+            .etags
+            .iter()
+            .filter_map(|(href, etag)| {
+                let data = self.objects.get(href)?;
+
+                if !matches(data) {
+                    return None;
+                }
+
+                let mut object = crate::Object::new(href.clone(), &BTreeMap::new());
+                object.set_auth(calendar.auth());
+                object.etag = Some(etag.clone());
+                object.data = Some(data.clone());
+
+                Some(object)
+            })
+            .collect();
+
+        crate::object::Iterator::from_snapshot(objects, self.taken)
+    }
+}
@@ -0,0 +1,384 @@
+use crate::Children;
+use crate::Requestable;
+use crate::Xmlable;
+use std::collections::BTreeMap;
+
+const MAGIC: &[u8; 2] = b"KD";
+const VERSION: u8 = 2;
+
+/**
+ * What is known of a calendar at the end of a synchronization.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CalendarState {
+    pub name: String,
+    pub url: String,
+    pub color: Option<String>,
+    pub ctag: Option<String>,
+    pub sync_token: Option<String>,
+    pub components: Vec<String>,
+    /**
+     * ETags of the objects, by href.
+     */
+    pub etags: BTreeMap<String, String>,
+}
+
+impl CalendarState {
+    fn new(name: &str, calendar: &crate::Calendar) -> Self {
+        Self {
+            name: name.to_string(),
+            url: calendar.url().to_string(),
+            color: calendar.color.clone(),
+            ctag: calendar.ctag.clone(),
+            sync_token: calendar.sync_token.clone(),
+            components: calendar.components.clone(),
+            etags: BTreeMap::new(),
+        }
+    }
+
+    fn tag(&self) -> Option<&str> {
+        self.sync_token.as_deref().or(self.ctag.as_deref())
+    }
+}
+
+/**
+ * Discovery and synchronization state, small enough to be kept in RTC memory
+ * or NVS while the device deep-sleeps.
+ *
+ * Credentials aren't part of the state, they are taken from the client by
+ * [`crate::Client::resume`].
+ */
+#[derive(Clone, Debug, Default)]
+pub struct State {
+    pub principal: String,
+    pub homes: Vec<String>,
+    pub calendars: Vec<CalendarState>,
+    auth: Option<crate::Authorization>,
+}
+
+impl State {
+    pub(crate) fn new(principal: &crate::Principal, homes: &[crate::Home]) -> Self {
+        Self {
+            principal: principal.url().to_string(),
+            homes: homes.iter().map(|x| x.url().to_string()).collect(),
+            calendars: Vec::new(),
+            auth: principal.auth(),
+        }
+    }
+
+    pub(crate) fn set_auth(&mut self, auth: Option<crate::Authorization>) {
+        self.auth = auth;
+    }
+
+    pub fn principal(&self) -> crate::Principal {
+        self.child(&self.principal)
+    }
+
+    pub fn homes(&self) -> Vec<crate::Home> {
+        self.homes.iter().map(|x| self.child(x)).collect()
+    }
+
+    /**
+     * The calendars as they were at the last synchronization, without any
+     * request, named as by [`crate::Client::calendars`].
+     */
+    pub fn calendars(&self) -> BTreeMap<String, crate::Calendar> {
+        self.calendars
+            .iter()
+            .map(|x| (x.name.clone(), self.calendar(x)))
+            .collect()
+    }
+
+    pub(crate) fn calendar(&self, state: &CalendarState) -> crate::Calendar {
+        let mut params = BTreeMap::new();
+
+        for (key, value) in [
+            ("color", &state.color),
+            ("ctag", &state.ctag),
+            ("sync_token", &state.sync_token),
+        ] {
+            if let Some(value) = value {
+                params.insert(key.to_string(), value.clone());
+            }
+        }
+
+        let mut calendar = crate::Calendar::new(state.url.clone(), &params);
+        calendar.set_auth(self.auth.clone());
+        calendar.components = state.components.clone();
+
+        calendar
+    }
+
+    fn child<C>(&self, url: &str) -> C
+    where
+        C: Children,
+    {
+        let mut child = C::new(url, &BTreeMap::new());
+        child.set_auth(self.auth.clone());
+
+        child
+    }
+
+    /**
+     * Checks the calendars with a single `PROPFIND` per home, then lists the
+     * ETags of the changed ones. Returns the changed and new calendars.
+     */
+    pub fn refresh(&mut self) -> crate::Result<Vec<crate::Calendar>> {
+        self.update(true)
+    }
+
+    /**
+     * Like [`State::refresh`], without listing the ETags of the changed
+     * calendars.
+     */
+    pub(crate) fn list(&mut self) -> crate::Result<Vec<crate::Calendar>> {
+        self.update(false)
+    }
+
+    fn update(&mut self, etags: bool) -> crate::Result<Vec<crate::Calendar>> {
+        let remote = crate::client::calendars(&self.homes())?;
+        let mut changed = Vec::new();
+
+        for (name, calendar) in &remote {
+            let current = self.calendars.iter().position(|x| x.url == calendar.url());
+
+            let unchanged = current
+                .map(|i| &self.calendars[i])
+                .is_some_and(|x| calendar.tag().is_some() && x.tag() == calendar.tag());
+
+            if unchanged {
+                // The name may have been suffixed since
+                if let Some(i) = current {
+                    self.calendars[i].name = name.clone();
+                }
+
+                continue;
+            }
+
+            let mut state = CalendarState::new(name, calendar);
+
+            if etags {
+                state.etags = calendar.etags()?;
+            }
+
+            match current {
+                Some(i) => self.calendars[i] = state,
+                None => self.calendars.push(state),
+            }
+
+            changed.push(calendar.clone());
+        }
+
+        self.calendars
+            .retain(|x| remote.values().any(|calendar| calendar.url() == x.url));
+
+        Ok(changed)
+    }
+
+    /**
+     * Serializes the state: URLs on the principal host are stored as paths
+     * and object hrefs relative to their calendar.
+     */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let origin = origin(&self.principal);
+        let mut writer = Writer(MAGIC.to_vec());
+
+        writer.0.push(VERSION);
+        writer.string(origin);
+        writer.string(relative(&self.principal, origin));
+        writer.len(self.homes.len());
+
+        for home in &self.homes {
+            writer.string(relative(home, origin));
+        }
+
+        writer.len(self.calendars.len());
+
+        for calendar in &self.calendars {
+            writer.string(&calendar.name);
+            writer.string(relative(&calendar.url, origin));
+            writer.option(&calendar.color);
+            writer.option(&calendar.ctag);
+            writer.option(&calendar.sync_token);
+            writer.len(calendar.components.len());
+
+            for component in &calendar.components {
+                writer.string(component);
+            }
+
+            writer.len(calendar.etags.len());
+
+            for (href, etag) in &calendar.etags {
+                writer.string(href.strip_prefix(calendar.url.as_str()).unwrap_or(href));
+                writer.string(etag);
+            }
+        }
+
+        writer.0
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(crate::Error::new("Invalid state"));
+        }
+
+        let version = reader.take(1)?[0];
+
+        // Version 1 had a single home
+        if version != 1 && version != VERSION {
+            return Err(crate::Error::Misc(format!(
+                "Unsupported state version {version}"
+            )));
+        }
+
+        let origin = reader.string()?;
+        let mut state = Self {
+            principal: absolute(reader.string()?, &origin),
+            ..Default::default()
+        };
+
+        let homes = if version == 1 { 1 } else { reader.len()? };
+
+        for _ in 0..homes {
+            state.homes.push(absolute(reader.string()?, &origin));
+        }
+
+        for _ in 0..reader.len()? {
+            let mut calendar = CalendarState {
+                name: reader.string()?,
+                url: absolute(reader.string()?, &origin),
+                color: reader.option()?,
+                ctag: reader.option()?,
+                sync_token: reader.option()?,
+                ..Default::default()
+            };
+
+            for _ in 0..reader.len()? {
+                calendar.components.push(reader.string()?);
+            }
+
+            for _ in 0..reader.len()? {
+                let href = reader.string()?;
+                let href = if href.contains("://") {
+                    href
+                } else {
+                    format!("{}{href}", calendar.url)
+                };
+
+                calendar.etags.insert(href, reader.string()?);
+            }
+
+            state.calendars.push(calendar);
+        }
+
+        Ok(state)
+    }
+}
+
+/**
+ * `scheme://host[:port]` of `url`.
+ */
+fn origin(url: &str) -> &str {
+    let path = crate::path(url);
+
+    if path.len() == url.len() {
+        ""
+    } else {
+        &url[..url.len() - path.len()]
+    }
+}
+
+fn relative<'a>(url: &'a str, origin: &str) -> &'a str {
+    match url.strip_prefix(origin) {
+        Some(path) if !origin.is_empty() && path.starts_with('/') => path,
+        _ => url,
+    }
+}
+
+fn absolute(url: String, origin: &str) -> String {
+    if url.starts_with('/') {
+        format!("{origin}{url}")
+    } else {
+        url
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    /**
+     * LEB128, a single byte below 128.
+     */
+    fn len(&mut self, mut len: usize) {
+        while len >= 0x80 {
+            self.0.push((len as u8 & 0x7f) | 0x80);
+            len >>= 7;
+        }
+
+        self.0.push(len as u8);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.len(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn option(&mut self, value: &Option<String>) {
+        match value {
+            Some(value) => {
+                self.0.push(1);
+                self.string(value);
+            }
+            None => self.0.push(0),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let value = self
+            .bytes
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| crate::Error::new("Truncated state"))?;
+
+        self.pos += len;
+
+        Ok(value)
+    }
+
+    fn len(&mut self) -> crate::Result<usize> {
+        let mut len = 0;
+
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.take(1)?[0];
+            len |= usize::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(len);
+            }
+        }
+
+        Err(crate::Error::new("Invalid state"))
+    }
+
+    fn string(&mut self) -> crate::Result<String> {
+        let len = self.len()?;
+        let value = self.take(len)?;
+
+        String::from_utf8(value.to_vec()).map_err(|_| crate::Error::new("Invalid state"))
+    }
+
+    fn option(&mut self) -> crate::Result<Option<String>> {
+        match self.take(1)?[0] {
+            0 => Ok(None),
+            _ => self.string().map(Some),
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use crate::Children;
+use crate::Requestable;
+use crate::Xmlable;
+
+/**
+ * A local modification waiting to be pushed to the server.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Create { href: String, data: String },
+    Update { href: String, data: String },
+    Delete { href: String },
+}
+
+impl Change {
+    pub fn href(&self) -> &str {
+        match self {
+            Self::Create { href, .. } | Self::Update { href, .. } | Self::Delete { href } => href,
+        }
+    }
+
+    fn data(&self) -> Option<&str> {
+        match self {
+            Self::Create { data, .. } | Self::Update { data, .. } => Some(data),
+            Self::Delete { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Server,
+    Client,
+}
+
+/**
+ * An object modified on both sides since the last synchronization. `None`
+ * means the object was deleted on that side.
+ */
+#[derive(Clone, Debug)]
+pub struct Conflict {
+    pub href: String,
+    pub local: Option<String>,
+    pub remote: Option<String>,
+}
+
+pub enum Policy {
+    ServerWins,
+    ClientWins,
+    /**
+     * Keeps the version with the highest `SEQUENCE`, then the most recent
+     * `DTSTAMP`. The server wins ties.
+     */
+    Newest,
+    Callback(Box<dyn FnMut(&Conflict) -> Resolution>),
+}
+
+impl Policy {
+    fn resolve(&mut self, conflict: &Conflict) -> Resolution {
+        match self {
+            Self::ServerWins => Resolution::Server,
+            Self::ClientWins => Resolution::Client,
+            Self::Newest => newest(conflict),
+            Self::Callback(f) => f(conflict),
+        }
+    }
+}
+
+impl std::fmt::Debug for Policy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            Self::ServerWins => "ServerWins",
+            Self::ClientWins => "ClientWins",
+            Self::Newest => "Newest",
+            Self::Callback(_) => "Callback",
+        };
+
+        f.write_str(s)
+    }
+}
+
+fn newest(conflict: &Conflict) -> Resolution {
+    let version = |data: &str| {
+        let calendars = crate::ics::parse(data);
+        let component = calendars
+            .iter()
+            .flat_map(|x| x.components.iter())
+            .find(|x| x.name != "VTIMEZONE");
+        let sequence = component
+            .and_then(|x| x.value("SEQUENCE"))
+            .and_then(|x| x.trim().parse::<i64>().ok())
+            .unwrap_or(0);
+        let dtstamp = component
+            .and_then(|x| x.value("DTSTAMP"))
+            .and_then(crate::ics::parse_date_time);
+
+        (sequence, dtstamp)
+    };
+
+    match (&conflict.local, &conflict.remote) {
+        (Some(local), Some(remote)) if version(local) > version(remote) => Resolution::Client,
+        // A modification is kept over a deletion
+        (Some(_), None) => Resolution::Client,
+        _ => Resolution::Server,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Created,
+    Updated,
+    Deleted,
+    /**
+     * Downloaded from the server.
+     */
+    Fetched,
+    /**
+     * Deleted on the server and removed from the cache.
+     */
+    Removed,
+    Resolved(Resolution),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Summary {
+    pub outcomes: Vec<(String, Outcome)>,
+}
+
+impl Summary {
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+
+    pub fn get(&self, href: &str) -> Option<Outcome> {
+        self.outcomes
+            .iter()
+            .find(|(x, _)| x == href)
+            .map(|(_, outcome)| *outcome)
+    }
+
+    pub fn conflicts(&self) -> impl std::iter::Iterator<Item = (&str, Resolution)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(href, outcome)| match outcome {
+                Outcome::Resolved(resolution) => Some((href.as_str(), *resolution)),
+                _ => None,
+            })
+    }
+}
+
+/**
+ * Two-way synchronization of a calendar with a local copy kept in a
+ * [`crate::Cache`].
+ *
+ * Local modifications are journaled in the storage, so they survive a reboot
+ * until [`SyncEngine::sync`] pushes them.
+ */
+pub struct SyncEngine<S>
+where
+    S: crate::Storage,
+{
+    calendar: crate::Calendar,
+    cache: crate::Cache<S>,
+    policy: Policy,
+    journal: Vec<Change>,
+}
+
+impl<S> SyncEngine<S>
+where
+    S: crate::Storage,
+{
+    pub fn new(calendar: crate::Calendar, storage: S, policy: Policy) -> crate::Result<Self> {
+        let cache = crate::Cache::new(storage);
+        let journal = cache
+            .storage()
+            .get(&Self::key(&calendar))?
+            .map(|x| decode(&x))
+            .unwrap_or_default();
+
+        Ok(Self {
+            calendar,
+            cache,
+            policy,
+            journal,
+        })
+    }
+
+    pub fn calendar(&self) -> &crate::Calendar {
+        &self.calendar
+    }
+
+    pub fn into_inner(self) -> S {
+        self.cache.into_inner()
+    }
+
+    /**
+     * Local changes not pushed yet.
+     */
+    pub fn journal(&self) -> &[Change] {
+        &self.journal
+    }
+
+    /**
+     * The local copy of `href`, with the pending changes.
+     */
+    pub fn data(&self, href: &str) -> crate::Result<Option<String>> {
+        if let Some(change) = self.journal.iter().rev().find(|x| x.href() == href) {
+            return Ok(change.data().map(str::to_string));
+        }
+
+        Ok(self.cache.get(href)?.map(|(_, data)| data))
+    }
+
+    /**
+     * Journals a new object, returns its href.
+     */
+    pub fn create(&mut self, data: &str) -> crate::Result<String> {
+        let uid = crate::ics::uid_of(data)
+            .ok_or_else(|| crate::Error::new("Calendar object without UID"))?;
+        let href = self.calendar.object_url(&uid);
+
+        self.record(Change::Create {
+            href: href.clone(),
+            data: data.to_string(),
+        })?;
+
+        Ok(href)
+    }
+
+    pub fn update(&mut self, href: &str, data: &str) -> crate::Result {
+        self.record(Change::Update {
+            href: href.to_string(),
+            data: data.to_string(),
+        })
+    }
+
+    pub fn delete(&mut self, href: &str) -> crate::Result {
+        self.record(Change::Delete {
+            href: href.to_string(),
+        })
+    }
+
+    /**
+     * Keeps one change per href: an update of a created object is still a
+     * creation and a created then deleted object is forgotten.
+     */
+    fn record(&mut self, change: Change) -> crate::Result {
+        let previous = self
+            .journal
+            .iter()
+            .position(|x| x.href() == change.href())
+            .map(|i| self.journal.remove(i));
+
+        let change = match (previous, change) {
+            (Some(Change::Create { .. }), Change::Delete { .. }) => None,
+            (Some(Change::Create { href, .. }), Change::Update { data, .. }) => {
+                Some(Change::Create { href, data })
+            }
+            (Some(Change::Delete { href }), Change::Create { data, .. }) => {
+                Some(Change::Update { href, data })
+            }
+            (_, change) => Some(change),
+        };
+
+        self.journal.extend(change);
+        self.save()
+    }
+
+    fn save(&mut self) -> crate::Result {
+        let key = Self::key(&self.calendar);
+
+        if self.journal.is_empty() {
+            self.cache.storage_mut().remove(&key)
+        } else {
+            self.cache.storage_mut().set(&key, &encode(&self.journal))
+        }
+    }
+
+    fn key(calendar: &crate::Calendar) -> String {
+        format!("journal:{}", calendar.url())
+    }
+
+    /**
+     * Pushes the journal, then downloads the remote changes.
+     *
+     * Each change leaves the journal once pushed: if the connection drops,
+     * the next call starts again with the remaining ones.
+     */
+    pub fn sync(&mut self) -> crate::Result<Summary> {
+        let mut summary = Summary::default();
+
+        while let Some(change) = self.journal.first().cloned() {
+            let outcome = self.push(&change)?;
+
+            summary.outcomes.push((change.href().to_string(), outcome));
+            self.journal.remove(0);
+            self.save()?;
+        }
+
+        let pull = self.calendar.pull(None, &mut self.cache)?;
+
+        let pulled = pull
+            .fetched
+            .into_iter()
+            .map(|x| (x, Outcome::Fetched))
+            .chain(pull.removed.into_iter().map(|x| (x, Outcome::Removed)));
+
+        for (href, outcome) in pulled {
+            if summary.get(&href).is_none() {
+                summary.outcomes.push((href, outcome));
+            }
+        }
+
+        Ok(summary)
+    }
+
+    fn push(&mut self, change: &Change) -> crate::Result<Outcome> {
+        let href = change.href();
+        let etag = self.cache.get(href)?.map(|(etag, _)| etag);
+
+        let result = match change {
+            Change::Create { data, .. } => self.calendar.put(href, data, None).map(Some),
+            Change::Update { data, .. } => match etag.as_deref() {
+                Some(etag) => self.calendar.put(href, data, Some(etag)).map(Some),
+                // Without a known version there is nothing to compare to,
+                // `If-None-Match: *` would always fail
+                None => {
+                    let mut headers = std::collections::BTreeMap::new();
+                    headers.insert("Content-Type", "text/calendar; charset=utf-8");
+
+                    self.calendar
+                        .send("PUT", href, Some(data), Some(headers))
+                        .map(Some)
+                }
+            },
+            Change::Delete { .. } => {
+                match Requestable::delete(&self.calendar, href, etag.as_deref()) {
+                    Err(err) if err.status() == Some(404) => Ok(None),
+                    result => result.map(|_| None),
+                }
+            }
+        };
+
+        match result {
+            Ok(response) => {
+                self.stored(href, response.as_ref(), change.data())?;
+
+                let outcome = match change {
+                    Change::Create { .. } => Outcome::Created,
+                    Change::Update { .. } => Outcome::Updated,
+                    Change::Delete { .. } => Outcome::Deleted,
+                };
+
+                Ok(outcome)
+            }
+            Err(err) if err.status() == Some(412) => self.conflict(href, change.data()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /**
+     * The precondition failed: the object changed on the server since the
+     * last synchronization.
+     */
+    fn conflict(&mut self, href: &str, local: Option<&str>) -> crate::Result<Outcome> {
+        let mut remote = crate::Object::new(href, &Default::default());
+        remote.set_auth(self.calendar.auth());
+
+        match remote.fetch() {
+            Err(err) if err.status() == Some(404 | 410) => remote.data = None,
+            result => result?,
+        }
+
+        let conflict = Conflict {
+            href: href.to_string(),
+            local: local.map(str::to_string),
+            remote: remote.data.clone(),
+        };
+
+        let resolution = self.policy.resolve(&conflict);
+
+        if resolution == Resolution::Client {
+            match local {
+                Some(data) => {
+                    let response = self.calendar.put(href, data, remote.etag())?;
+                    self.stored(href, Some(&response), Some(data))?;
+                }
+                None if remote.data.is_some() => {
+                    Requestable::delete(&self.calendar, href, remote.etag())?;
+                    self.stored(href, None, None)?;
+                }
+                None => (),
+            }
+        }
+
+        // Otherwise the remote version is downloaded by the pull
+
+        Ok(Outcome::Resolved(resolution))
+    }
+
+    /**
+     * Updates the cache after a push. Without ETag the server modified the
+     * object, it's downloaded again by the pull.
+     */
+    fn stored(
+        &mut self,
+        href: &str,
+        response: Option<&crate::Response>,
+        data: Option<&str>,
+    ) -> crate::Result {
+        let etag = response.and_then(|x| x.header("ETag"));
+
+        match (etag, data) {
+            (Some(etag), Some(data)) => self.cache.store(href, etag, data),
+            _ => self.cache.remove(href),
+        }
+    }
+}
+
+/**
+ * One change per record: a `kind\thref\tlength` line followed by `length`
+ * bytes of data.
+ */
+fn encode(journal: &[Change]) -> Vec<u8> {
+    let mut text = String::new();
+
+    for change in journal {
+        let kind = match change {
+            Change::Create { .. } => 'C',
+            Change::Update { .. } => 'U',
+            Change::Delete { .. } => 'D',
+        };
+        let data = change.data().unwrap_or_default();
+
+        text.push_str(&format!(
+            "{kind}\t{}\t{}\n{data}",
+            change.href(),
+            data.len()
+        ));
+    }
+
+    text.into_bytes()
+}
+
+fn decode(bytes: &[u8]) -> Vec<Change> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut rest: &str = &text;
+    let mut journal = Vec::new();
+
+    while let Some((header, tail)) = rest.split_once('\n') {
+        let mut fields = header.split('\t');
+
+        let (Some(kind), Some(href), Some(len)) = (
+            fields.next(),
+            fields.next(),
+            fields.next().and_then(|x| x.parse::<usize>().ok()),
+        ) else {
+            break;
+        };
+
+        let Some(data) = tail.get(..len) else {
+            break;
+        };
+
+        let href = href.to_string();
+        let data = data.to_string();
+
+        let change = match kind {
+            "C" => Change::Create { href, data },
+            "U" => Change::Update { href, data },
+            "D" => Change::Delete { href },
+            _ => break,
+        };
+
+        journal.push(change);
+        rest = &tail[len..];
+    }
+
+    journal
+}
@@ -0,0 +1,545 @@
+use chrono::{Datelike, Offset, TimeZone};
+use std::collections::BTreeMap;
+
+/**
+ * A timezone, to convert between wall clock and UTC times.
+ */
+#[derive(Clone, Debug)]
+pub enum Zone {
+    Fixed(chrono::FixedOffset),
+    VTimezone(VTimezone),
+    #[cfg(feature = "chrono-tz")]
+    Iana(chrono_tz::Tz),
+}
+
+impl Default for Zone {
+    fn default() -> Self {
+        Self::Fixed(chrono::Utc.fix())
+    }
+}
+
+impl Zone {
+    /**
+     * Offset of a wall clock time, the first one when the time is repeated
+     * at the end of the daylight saving time.
+     */
+    pub fn offset_from_local(&self, local: chrono::NaiveDateTime) -> chrono::FixedOffset {
+        match self {
+            Self::Fixed(offset) => *offset,
+            Self::VTimezone(vtimezone) => vtimezone.offset(local, false),
+            #[cfg(feature = "chrono-tz")]
+            Self::Iana(tz) => tz
+                .offset_from_local_datetime(&local)
+                .earliest()
+                .map(|x| x.fix())
+                // Skipped at the start of the daylight saving time
+                .unwrap_or_else(|| tz.offset_from_utc_datetime(&local).fix()),
+        }
+    }
+
+    pub fn offset_from_utc(&self, utc: chrono::NaiveDateTime) -> chrono::FixedOffset {
+        match self {
+            Self::Fixed(offset) => *offset,
+            Self::VTimezone(vtimezone) => vtimezone.offset(utc, true),
+            #[cfg(feature = "chrono-tz")]
+            Self::Iana(tz) => tz.offset_from_utc_datetime(&utc).fix(),
+        }
+    }
+
+    pub fn from_local(&self, local: chrono::NaiveDateTime) -> chrono::DateTime<chrono::Utc> {
+        let offset = self.offset_from_local(local);
+
+        chrono::Utc.from_utc_datetime(&(local - offset))
+    }
+
+    /**
+     * `date_time` in this zone, for display.
+     */
+    pub fn to_local<Tz>(
+        &self,
+        date_time: &chrono::DateTime<Tz>,
+    ) -> chrono::DateTime<chrono::FixedOffset>
+    where
+        Tz: chrono::TimeZone,
+    {
+        let offset = self.offset_from_utc(date_time.naive_utc());
+
+        date_time.with_timezone(&offset)
+    }
+}
+
+/**
+ * A `VTIMEZONE` component (RFC 5545 §3.6.5). Only the yearly rules are
+ * evaluated, which is enough for the timezones in use.
+ */
+#[derive(Clone, Debug)]
+pub struct VTimezone {
+    pub tzid: String,
+    observances: Vec<Observance>,
+}
+
+#[derive(Clone, Debug)]
+struct Observance {
+    start: chrono::NaiveDateTime,
+    offset_from: chrono::FixedOffset,
+    offset_to: chrono::FixedOffset,
+    rule: Option<Rule>,
+    rdates: Vec<chrono::NaiveDateTime>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Rule {
+    month: Option<u32>,
+    ordinal: Option<i32>,
+    weekday: Option<chrono::Weekday>,
+    month_days: Vec<i32>,
+    until: Option<chrono::NaiveDateTime>,
+    count: Option<i32>,
+}
+
+impl VTimezone {
+    pub(crate) fn from_component(component: &crate::ics::Component) -> Option<Self> {
+        let observances = component
+            .components
+            .iter()
+            .filter(|x| x.name == "STANDARD" || x.name == "DAYLIGHT")
+            .filter_map(Observance::from_component)
+            .collect::<Vec<_>>();
+
+        if observances.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            tzid: component.value("TZID")?.to_string(),
+            observances,
+        })
+    }
+
+    /**
+     * The offset of the last observance started before `date_time`, a wall
+     * clock time unless `utc`.
+     */
+    fn offset(&self, date_time: chrono::NaiveDateTime, utc: bool) -> chrono::FixedOffset {
+        let current = self
+            .observances
+            .iter()
+            .filter_map(|x| Some((x.onset(date_time, utc)?, x)))
+            .max_by_key(|(onset, _)| *onset);
+
+        match current {
+            Some((_, observance)) => observance.offset_to,
+            None => self
+                .observances
+                .iter()
+                .min_by_key(|x| x.start)
+                .map(|x| x.offset_from)
+                .unwrap_or_else(|| chrono::Utc.fix()),
+        }
+    }
+}
+
+impl Observance {
+    fn from_component(component: &crate::ics::Component) -> Option<Self> {
+        let start = crate::ics::parse_date_time(component.value("DTSTART")?)?.naive_utc();
+
+        let rdates = component
+            .properties("RDATE")
+            .flat_map(|x| x.value.split(','))
+            .filter_map(crate::ics::parse_date_time)
+            .map(|x| x.naive_utc())
+            .collect();
+
+        Some(Self {
+            start,
+            offset_from: parse_offset(component.value("TZOFFSETFROM")?)?,
+            offset_to: parse_offset(component.value("TZOFFSETTO")?)?,
+            rule: component.value("RRULE").and_then(Rule::parse),
+            rdates,
+        })
+    }
+
+    /**
+     * The last start of this observance before `date_time`, in wall clock
+     * time unless `utc`.
+     */
+    fn onset(&self, date_time: chrono::NaiveDateTime, utc: bool) -> Option<chrono::NaiveDateTime> {
+        // Onsets are in the wall clock time of the previous observance
+        let date_time = if utc {
+            date_time + self.offset_from
+        } else {
+            date_time
+        };
+
+        let mut onsets = vec![self.start];
+        onsets.extend(self.rdates.iter().copied());
+
+        if let Some(rule) = &self.rule {
+            for year in [date_time.year() - 1, date_time.year()] {
+                onsets.extend(rule.occurrence(self.start, year));
+            }
+        }
+
+        onsets
+            .into_iter()
+            .filter(|x| *x <= date_time && *x >= self.start)
+            .max()
+    }
+}
+
+impl Rule {
+    fn parse(rrule: &str) -> Option<Self> {
+        let mut rule = Self::default();
+        let mut yearly = false;
+
+        for part in rrule.split(';') {
+            let (key, value) = part.split_once('=')?;
+
+            match key.to_uppercase().as_str() {
+                "FREQ" => yearly = value.eq_ignore_ascii_case("YEARLY"),
+                "BYMONTH" => rule.month = value.parse().ok(),
+                "BYMONTHDAY" => {
+                    rule.month_days = value.split(',').filter_map(|x| x.parse().ok()).collect()
+                }
+                "BYDAY" => {
+                    let split = value.len().checked_sub(2)?;
+                    let (ordinal, weekday) = value.split_at(split);
+
+                    rule.ordinal = ordinal.parse().ok();
+                    rule.weekday = parse_weekday(weekday);
+                }
+                "UNTIL" => rule.until = crate::ics::parse_date_time(value).map(|x| x.naive_utc()),
+                "COUNT" => rule.count = value.parse().ok(),
+                _ => (),
+            }
+        }
+
+        yearly.then_some(rule)
+    }
+
+    fn occurrence(&self, start: chrono::NaiveDateTime, year: i32) -> Option<chrono::NaiveDateTime> {
+        if self.count.is_some_and(|count| year - start.year() >= count) {
+            return None;
+        }
+
+        let month = self.month.unwrap_or(start.month());
+        let first = chrono::NaiveDate::from_ymd_opt(year, month, 1)?;
+        let days = first
+            .iter_days()
+            .take_while(|x| x.month() == month)
+            .collect::<Vec<_>>();
+        let len = days.len() as i32;
+
+        let candidates = days
+            .into_iter()
+            .filter(|x| {
+                let day = x.day() as i32;
+
+                self.month_days.is_empty()
+                    || self
+                        .month_days
+                        .iter()
+                        .any(|n| *n == day || *n == day - len - 1)
+            })
+            .filter(|x| self.weekday.is_none_or(|weekday| x.weekday() == weekday))
+            .collect::<Vec<_>>();
+
+        let date = match self.ordinal {
+            Some(n) if n > 0 => candidates.get(n as usize - 1).copied(),
+            Some(n) => candidates.iter().rev().nth((-n - 1) as usize).copied(),
+            None if self.weekday.is_none() && self.month_days.is_empty() => {
+                chrono::NaiveDate::from_ymd_opt(year, month, start.day())
+            }
+            None => candidates.first().copied(),
+        }?;
+
+        let occurrence = date.and_time(start.time());
+
+        if self.until.is_some_and(|until| occurrence > until) {
+            return None;
+        }
+
+        Some(occurrence)
+    }
+}
+
+fn parse_offset(value: &str) -> Option<chrono::FixedOffset> {
+    let value = value.trim();
+    let (sign, digits) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+
+    let field = |range: std::ops::Range<usize>| -> Option<i32> {
+        match digits.get(range) {
+            Some("") | None => Some(0),
+            Some(x) => x.parse().ok(),
+        }
+    };
+
+    if digits.len() < 4 {
+        return None;
+    }
+
+    let seconds = field(0..2)? * 3600 + field(2..4)? * 60 + field(4..6)?;
+
+    chrono::FixedOffset::east_opt(sign * seconds)
+}
+
+fn parse_weekday(value: &str) -> Option<chrono::Weekday> {
+    let weekday = match value.to_uppercase().as_str() {
+        "MO" => chrono::Weekday::Mon,
+        "TU" => chrono::Weekday::Tue,
+        "WE" => chrono::Weekday::Wed,
+        "TH" => chrono::Weekday::Thu,
+        "FR" => chrono::Weekday::Fri,
+        "SA" => chrono::Weekday::Sat,
+        "SU" => chrono::Weekday::Sun,
+        _ => return None,
+    };
+
+    Some(weekday)
+}
+
+/**
+ * Resolves the `TZID` of date-times: from the `VTIMEZONE` of the object,
+ * then from the `calendar-timezone` of its calendar or a timezone service,
+ * then from the IANA database with the `chrono-tz` feature. Floating times,
+ * and times in an unknown timezone, are in the zone of the calendar, or of
+ * the device without one.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Timezones {
+    device: Zone,
+    calendar: Option<Zone>,
+    zones: BTreeMap<String, Zone>,
+}
+
+impl Timezones {
+    pub fn new(device: Zone) -> Self {
+        Self {
+            device,
+            calendar: None,
+            zones: BTreeMap::new(),
+        }
+    }
+
+    pub fn device(&self) -> &Zone {
+        &self.device
+    }
+
+    /**
+     * The `calendar-timezone` or `calendar-timezone-id` of the calendar
+     * (RFC 4791 §5.2.2, RFC 7809 §5.2), if resolved.
+     */
+    pub fn calendar(&self) -> Option<&Zone> {
+        self.calendar.as_ref()
+    }
+
+    pub(crate) fn set_calendar(&mut self, zone: Zone) {
+        self.calendar = Some(zone);
+    }
+
+    /**
+     * The zone of floating times: the calendar one first, then the device
+     * one.
+     */
+    fn floating(&self) -> Zone {
+        self.calendar.clone().unwrap_or_else(|| self.device.clone())
+    }
+
+    /**
+     * Adds the `VTIMEZONE`s of an iCalendar stream, replacing the ones with
+     * the same `TZID`.
+     */
+    pub fn add(&mut self, data: &str) {
+        for vcalendar in crate::ics::parse(data) {
+            self.add_component(&vcalendar);
+        }
+    }
+
+    fn add_component(&mut self, vcalendar: &crate::ics::Component) {
+        let vtimezones = vcalendar
+            .children("VTIMEZONE")
+            .filter_map(VTimezone::from_component);
+
+        for vtimezone in vtimezones {
+            self.zones
+                .insert(vtimezone.tzid.clone(), Zone::VTimezone(vtimezone));
+        }
+    }
+
+    /**
+     * The `VTIMEZONE`s of `vcalendar`, floating times are in UTC. Used where
+     * no device zone is known, like ikal does with the embedded zones.
+     */
+    pub(crate) fn embedded(vcalendar: &crate::ics::Component) -> Self {
+        let mut timezones = Self::default();
+        timezones.add_component(vcalendar);
+
+        timezones
+    }
+
+    /**
+     * Downloads from the first of `services` that knows them (RFC 7808) the
+     * zones used by `data` but not defined, for servers omitting standard
+     * `VTIMEZONE`s (RFC 7809). See [`crate::Home::timezone_services`].
+     */
+    pub fn fetch(&mut self, data: &str, services: &[String]) -> crate::Result {
+        let mut tzids = Vec::new();
+        let mut timezones = self.clone();
+        timezones.add(data);
+
+        for vcalendar in crate::ics::parse(data) {
+            referenced(&vcalendar, &mut tzids);
+        }
+
+        tzids.retain(|x| timezones.zone(x).is_none());
+        tzids.sort();
+        tzids.dedup();
+
+        for tzid in tzids {
+            self.download(&tzid, services)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * The zone `tzid`, downloaded from the first of `services` that knows it
+     * when it isn't defined.
+     */
+    pub(crate) fn fetch_zone(
+        &mut self,
+        tzid: &str,
+        services: &[String],
+    ) -> crate::Result<Option<Zone>> {
+        if self.zone(tzid).is_none() {
+            self.download(tzid, services)?;
+        }
+
+        Ok(self.zone(tzid))
+    }
+
+    fn download(&mut self, tzid: &str, services: &[String]) -> crate::Result {
+        for service in services {
+            let url = format!(
+                "{}/zones/{}",
+                service.trim_end_matches('/'),
+                tzid.replace(' ', "%20")
+            );
+            // The credentials of the CalDAV server aren't sent to the service
+            let object: crate::Object = crate::Children::new(url.clone(), &Default::default());
+
+            match crate::Requestable::get(&object, url) {
+                Ok(body) => {
+                    self.add(&body);
+                    break;
+                }
+                Err(err) if err.status().is_some() => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn zone(&self, tzid: &str) -> Option<Zone> {
+        if let Some(zone) = self.zones.get(tzid) {
+            return Some(zone.clone());
+        }
+
+        iana(tzid)
+    }
+
+    /**
+     * Converts a `DATE-TIME` or `DATE` value with an optional `TZID` to UTC.
+     * A date is the midnight of the floating times zone.
+     */
+    pub fn date_time(
+        &self,
+        value: &str,
+        tzid: Option<&str>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        let value = value.trim();
+        let date_time = crate::ics::parse_date_time(value)?;
+
+        if value.ends_with('Z') {
+            return Some(date_time);
+        }
+
+        let zone = tzid
+            .and_then(|x| self.zone(x))
+            .unwrap_or_else(|| self.floating());
+
+        Some(zone.from_local(date_time.naive_utc()))
+    }
+
+    pub(crate) fn property(
+        &self,
+        property: &crate::ics::Property,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.date_time(&property.value, property.param("TZID"))
+    }
+
+    /**
+     * Start and end of the first event or task of `data`. A `TZID` is looked
+     * up in the `VTIMEZONE`s of `data`, then in the known zones; floating
+     * times are in the calendar zone, then in the device zone.
+     */
+    pub fn bounds(
+        &self,
+        data: &str,
+    ) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+        let mut timezones = self.clone();
+        timezones.add(data);
+
+        let calendars = crate::ics::parse(data);
+        let component = calendars
+            .iter()
+            .flat_map(|x| x.components.iter())
+            .find(|x| x.name != "VTIMEZONE")?;
+
+        timezones.component_bounds(component)
+    }
+
+    pub(crate) fn component_bounds(
+        &self,
+        component: &crate::ics::Component,
+    ) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+        crate::ics::bounds_with(component, |x| self.property(x))
+    }
+}
+
+fn referenced(component: &crate::ics::Component, tzids: &mut Vec<String>) {
+    if component.name == "VTIMEZONE" {
+        return;
+    }
+
+    let params = component.properties.iter().filter_map(|x| x.param("TZID"));
+    tzids.extend(params.map(str::to_string));
+
+    for child in &component.components {
+        referenced(child, tzids);
+    }
+}
+
+#[cfg(feature = "chrono-tz")]
+fn iana(tzid: &str) -> Option<Zone> {
+    // Some clients prefix the name, like `/mozilla.org/20050126_1/Europe/Paris`
+    let mut name = tzid.trim_start_matches('/');
+
+    loop {
+        if let Ok(tz) = name.parse::<chrono_tz::Tz>() {
+            return Some(Zone::Iana(tz));
+        }
+
+        name = name.split_once('/')?.1;
+    }
+}
+
+#[cfg(not(feature = "chrono-tz"))]
+fn iana(_: &str) -> Option<Zone> {
+    None
+}